@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+use rand::{SeedableRng, rngs::StdRng};
+use square_word::{cli::EstimateArgs, finder::estimate_search, get_words};
+
+fn main() {
+    let args = EstimateArgs::from_env();
+    let valid_words = get_words().unwrap();
+    let n = args.word_limit.unwrap_or(valid_words.len());
+    let valid_words: Vec<&str> = valid_words.iter().take(n).map(|s| s.as_str()).collect();
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    eprintln!("seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let estimate =
+        estimate_search::<5>(&valid_words, args.samples, Duration::from_secs(5), &mut rng);
+    print!("{estimate}");
+}
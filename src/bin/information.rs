@@ -1,24 +1,13 @@
-use ascii::AsciiString;
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use square_word::{
+    Solution, SolutionSet,
     first_guess::{distrobution_for, entropy},
-    Solution,
+    read_solutions,
 };
 
 fn main() {
     let lines = include_str!("../../solutions.txt");
-    let solutions = lines
-        .lines()
-        .map(|line| {
-            Solution::new(
-                line.split(',')
-                    .map(|word| AsciiString::from_ascii(word).unwrap())
-                    .collect::<Vec<_>>()
-                    .try_into()
-                    .unwrap(),
-            )
-        })
-        .collect::<Vec<_>>();
+    let solutions: Vec<Solution> = read_solutions(lines, SolutionSet::Expanded).unwrap();
 
     let possible_answers = include_str!("../../words.txt");
     let possible_answers = possible_answers.lines().collect::<Vec<&str>>();
@@ -0,0 +1,53 @@
+use std::{fs, io::Write};
+
+use square_word::{
+    cli::MergeArgs,
+    shard::{ShardFile, merge},
+};
+
+fn main() {
+    let args = MergeArgs::from_env();
+
+    let mut files = Vec::new();
+    for path in &args.shard_files {
+        let file = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| contents.parse::<ShardFile>());
+        match file {
+            Ok(file) => files.push(file),
+            Err(e) => {
+                eprintln!("Couldn't read {}: {e}", path.display());
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let solutions = match merge(&files) {
+        Ok(solutions) => solutions,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let merged = solutions
+        .iter()
+        .map(|sol| format!("{sol}\n"))
+        .collect::<String>();
+    let written = match &args.output {
+        Some(path) => fs::write(path, merged),
+        None => std::io::stdout().write_all(merged.as_bytes()),
+    };
+    if let Err(e) = written {
+        eprintln!("Couldn't write the merged solutions: {e}");
+        std::process::exit(1);
+    }
+
+    let found = files.iter().map(|file| file.solutions.len()).sum::<usize>();
+    eprintln!(
+        "Merged {} shards: {} solutions, {} duplicates removed",
+        files.len(),
+        solutions.len(),
+        found - solutions.len()
+    );
+}
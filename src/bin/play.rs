@@ -1,13 +1,13 @@
 use std::{fs::read_to_string, io::stdin};
 
 use ascii::{AsciiChar, AsciiString};
-use square_word::{finder::Puzzle, Solution};
+use square_word::{Solution, finder::Puzzle};
 
 fn main() {
     let lines = read_to_string("./solutions.txt").unwrap();
     let lines = lines.lines().collect::<Vec<_>>();
     let words = lines[0].split(',').collect::<Vec<_>>();
-    let sol = Solution::new(words.try_into().unwrap());
+    let sol: Solution = Solution::new(words.try_into().unwrap());
     let mut puzzle = Puzzle::new(sol);
     let mut vm = puzzle.view();
     let stdin = stdin();
@@ -0,0 +1,54 @@
+use std::fs;
+
+use square_word::{cli::RankArgs, commonness::Commonness, *};
+
+fn main() {
+    let args = RankArgs::from_env();
+
+    let set = match args.canonical {
+        true => SolutionSet::Canonical,
+        false => SolutionSet::Expanded,
+    };
+    let solutions = fs::read_to_string(&args.solutions)
+        .map_err(|e| e.to_string())
+        .and_then(|text| read_solutions(&text, set));
+    let solutions: Vec<Solution> = match solutions {
+        Ok(solutions) => solutions,
+        Err(e) => {
+            eprintln!("Couldn't read {}: {e}", args.solutions.display());
+            std::process::exit(1);
+        }
+    };
+    let words = get_words().unwrap_or_default();
+    let commonness = match Commonness::load(args.scores.as_deref(), &words) {
+        Ok(commonness) => commonness,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut ranked = solutions
+        .into_iter()
+        .map(|sol| (sol.score(&commonness), sol))
+        .filter(|(score, _)| {
+            args.min_score
+                .is_none_or(|min| score.by(args.score_by) >= min)
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|(a, a_sol), (b, b_sol)| {
+        b.by(args.score_by)
+            .total_cmp(&a.by(args.score_by))
+            .then_with(|| a_sol.cmp(b_sol))
+    });
+    let total = ranked.len();
+    ranked.truncate(args.top.unwrap_or(total));
+
+    for (score, sol) in &ranked {
+        match args.show_scores {
+            true => println!("{sol} {score}"),
+            false => println!("{sol}"),
+        }
+    }
+    eprintln!("Ranked {total} solutions, printed {}", ranked.len());
+}
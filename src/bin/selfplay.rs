@@ -0,0 +1,37 @@
+use std::fs;
+
+use rand::{SeedableRng, rngs::StdRng};
+use square_word::{
+    cli::SelfplayArgs,
+    selfplay::{dictionary_of, play_all, sample},
+    *,
+};
+
+fn main() {
+    let args = SelfplayArgs::from_env();
+
+    let solutions = fs::read_to_string(&args.solutions)
+        .map_err(|e| e.to_string())
+        .and_then(|text| read_solutions(&text, SolutionSet::Expanded));
+    let solutions: Vec<Solution> = match solutions {
+        Ok(solutions) => solutions,
+        Err(e) => {
+            eprintln!("Couldn't read {}: {e}", args.solutions.display());
+            std::process::exit(1);
+        }
+    };
+
+    let puzzles = match args.sample {
+        Some(count) => {
+            let seed = args.seed.unwrap_or_else(rand::random);
+            eprintln!("seed {seed}");
+            sample(&solutions, count, &mut StdRng::seed_from_u64(seed))
+        }
+        None => solutions.clone(),
+    };
+    let dictionary = dictionary_of(&solutions);
+    let strategy = args.strategy.create(solutions, dictionary);
+
+    let report = play_all(&puzzles, strategy.as_ref(), args.max_guesses, args.shown);
+    print!("{report}");
+}
@@ -1,25 +1,271 @@
-use std::env::args;
+use std::{sync::Arc, time::Duration};
 
-use square_word::{finder::*, *};
+use square_word::{
+    checkpoint::Checkpointer,
+    cli::SolveArgs,
+    commonness::Commonness,
+    finder::{new_double_sided::Constraints, *},
+    progress::StatusLine,
+    shard::{SHARD_COMPLETE, ShardHeader},
+    template::Template,
+    *,
+};
 
 fn main() {
-    let valid_words = get_words().unwrap();
-    let n = args()
-        .nth(1)
-        .map(|s| s.parse().unwrap_or(valid_words.len()))
-        .unwrap_or(valid_words.len());
-    let valid_words: Vec<&str> = valid_words.iter().take(n).map(|s| s.as_str()).collect();
-
-    find_solutions::<DoubleSidedFinder<BinSearchRange>>(&valid_words);
+    let args = SolveArgs::from_env();
+    if let Err(e) = check_supported(&args) {
+        eprintln!("{e}");
+        std::process::exit(2);
+    }
+    match args.size() {
+        3 => solve::<3>(&args, Template::default(), None),
+        4 => solve::<4>(&args, Template::default(), None),
+        5 => solve(&args, args.template, Some(double_sided)),
+        6 => solve::<6>(&args, Template::default(), None),
+        7 => solve::<7>(&args, Template::default(), None),
+        size => unreachable!("--size {size} should have been turned down"),
+    }
+}
+
+/// Builds the [`DoubleSidedFinder`] of a kind, which only search 5 by 5 grids
+type DoubleSided<const N: usize> =
+    fn(FinderKind, &[&str], &SolveArgs) -> Box<dyn FirstRowFinder<N>>;
+
+/// Searches `N` by `N` grids. Only `template`'s letters are fixed, and `double_sided` builds
+/// the double-sided finders if they can search grids this size.
+fn solve<const N: usize>(
+    args: &SolveArgs,
+    template: Template<N>,
+    double_sided: Option<DoubleSided<N>>,
+) {
+    let all_words = match &args.words {
+        Some(path) => read_words_of_length(path, N),
+        None => get_words_of_length(N),
+    };
+    let all_words = match all_words {
+        Ok(words) if words.is_empty() => {
+            eprintln!("The word list doesn't have any {N} letter words");
+            std::process::exit(1);
+        }
+        Ok(words) => words,
+        Err(e) => {
+            eprintln!("Couldn't read the word list: {e}");
+            std::process::exit(1);
+        }
+    };
+    let n = args.word_limit.unwrap_or(all_words.len());
+    let valid_words: Vec<&str> = all_words.iter().take(n).map(|s| s.as_str()).collect();
+
+    let commonness = match Commonness::load(args.scores.as_deref(), &all_words) {
+        Ok(commonness) => commonness,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let keep = |sol: &Solution<N>| {
+        template.matches(sol)
+            && args
+                .min_score
+                .is_none_or(|min| sol.score(&commonness).by(args.score_by) >= min)
+    };
+
+    match args.finder {
+        FinderKind::NewDoubleSided => {
+            let constraints = Constraints {
+                template,
+                duplicates: args.duplicates,
+                shard: args.shard.unwrap_or_default(),
+                canonical: args.canonical,
+                rules: args.rules.clone(),
+                letters: args.letters,
+            };
+            let finder = NewDoubleSidedFinder::new(&valid_words).with_constraints(constraints);
+            search(&finder, args, &valid_words, &commonness, &keep);
+        }
+        kind @ (FinderKind::DoubleSidedBin
+        | FinderKind::DoubleSidedHash
+        | FinderKind::DoubleSidedLinear) => {
+            let double_sided =
+                double_sided.expect("check_supported only lets 5 by 5 searches use this finder");
+            let finder = double_sided(kind, &valid_words, args);
+            search(finder.as_ref(), args, &valid_words, &commonness, &keep);
+        }
+        kind => {
+            let allowed = valid_words
+                .iter()
+                .copied()
+                .filter(|word| args.rules.allows(word))
+                .collect::<Vec<_>>();
+            let print = |sol: Solution<N>| {
+                if keep(&sol) && args.rules.matches(&sol) {
+                    println!("{sol}");
+                }
+            };
+            match kind {
+                FinderKind::MostConstrained => MostConstrainedFinder::new(&allowed)
+                    .with_template(template)
+                    .find_each(&print),
+                kind => kind
+                    .create_sized(&allowed)
+                    .expect("every other finder searches any size of grid")
+                    .find_each(&print),
+            }
+        }
+    }
 }
 
-fn find_solutions<'a, T>(words: &'a [&'a str])
-where
-    T: SolutionFinder<'a>,
-{
-    let t = T::new(words);
-    let solutions = t.find();
-    for sol in solutions {
-        println!("{sol}");
+/// Rejects options the chosen finder can't honour
+fn check_supported(args: &SolveArgs) -> Result<(), String> {
+    let finder = args.finder;
+    if !finder.supports_size(args.size()) {
+        return Err(format!(
+            "{finder} only searches 5 by 5 grids, pick another --finder for --size {}",
+            args.size()
+        ));
+    }
+    let only_new = |option: &str| {
+        Err(format!(
+            "{option} is only supported by {}",
+            FinderKind::NewDoubleSided
+        ))
+    };
+    if finder != FinderKind::NewDoubleSided {
+        if args.stats {
+            return only_new("--stats");
+        }
+        if args.duplicates != DuplicatePolicy::Distinct {
+            return only_new("--duplicates");
+        }
+        if args.count && !args.template.is_blank() {
+            return only_new("--count with a --template");
+        }
+        if args.canonical && !args.template.is_blank() {
+            return only_new("--canonical with a --template");
+        }
+        if !args.letters.is_empty() {
+            return only_new("--max-repeats, --ban-letters and --require-letters");
+        }
+    }
+
+    let by_first_row = matches!(
+        finder,
+        FinderKind::DoubleSidedBin
+            | FinderKind::DoubleSidedHash
+            | FinderKind::DoubleSidedLinear
+            | FinderKind::NewDoubleSided
+    );
+    let needs_first_rows = [
+        ("--count", args.count),
+        ("--resume", args.resume.is_some()),
+        ("--shard", args.shard.is_some()),
+        ("--canonical", args.canonical),
+    ];
+    match needs_first_rows.iter().find(|(_, given)| *given) {
+        Some((option, _)) if !by_first_row => Err(format!(
+            "{option} needs a finder that searches one first row at a time, not {finder}"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The double-sided finder of this `kind` with the search's options
+fn double_sided(kind: FinderKind, words: &[&str], args: &SolveArgs) -> Box<dyn FirstRowFinder> {
+    match kind {
+        FinderKind::DoubleSidedBin => Box::new(double_sided_with::<BinSearchRange>(words, args)),
+        FinderKind::DoubleSidedHash => Box::new(double_sided_with::<HasSearchRange>(words, args)),
+        FinderKind::DoubleSidedLinear => {
+            Box::new(double_sided_with::<LinearSearchRange>(words, args))
+        }
+        kind => unreachable!("{kind} isn't a double-sided finder"),
+    }
+}
+
+fn double_sided_with<R: for<'a> RangeFinder<'a> + Send + Sync>(
+    words: &[&str],
+    args: &SolveArgs,
+) -> DoubleSidedFinder<R> {
+    let finder = DoubleSidedFinder::<R>::new(words)
+        .with_shard(args.shard.unwrap_or_default())
+        .with_rules(args.rules.clone());
+    match args.canonical {
+        true => finder.with_canonical_only(),
+        false => finder,
+    }
+}
+
+/// Runs a search that shows its progress and can be counted, split into shards or resumed,
+/// printing the solutions it should `keep`
+fn search<const N: usize>(
+    finder: &dyn FirstRowFinder<N>,
+    args: &SolveArgs,
+    words: &[&str],
+    scores: &Commonness,
+    keep: &(dyn Fn(&Solution<N>) -> bool + Sync),
+) {
+    let status = StatusLine::new(Duration::from_secs(10));
+    let observer = |progress| status.update(progress);
+
+    if args.count {
+        let (counts, stats) = finder.count_by_first_row(&observer);
+        print!("{counts}");
+        if args.stats {
+            eprint!("{stats}");
+        }
+        return;
+    }
+
+    if let Some(shard) = args.shard {
+        let header = ShardHeader {
+            shard,
+            words: words.len(),
+            search: args.finder.to_string(),
+            settings: args.fingerprint(words, scores),
+        };
+        println!("{header}");
+    }
+
+    let stats = match &args.resume {
+        Some(path) => {
+            let settings = args.checkpoint_fingerprint(words, scores);
+            let checkpointer = match Checkpointer::open(path, settings, Duration::from_secs(60)) {
+                Ok(checkpointer) => Arc::new(checkpointer),
+                Err(e) => {
+                    eprintln!("Couldn't resume from {}: {e}", path.display());
+                    std::process::exit(1);
+                }
+            };
+            checkpointer.save_on_interrupt().unwrap();
+
+            let stats = finder.find_by_first_row(
+                &|word| checkpointer.is_finished(word),
+                &|word, sols| {
+                    let sols = sols.into_iter().filter(|sol| keep(sol));
+                    checkpointer.finish(word, sols.collect())
+                },
+                &observer,
+            );
+            checkpointer.save().unwrap();
+
+            for sol in checkpointer.solutions() {
+                println!("{sol}");
+            }
+            stats
+        }
+        None => finder.find_each_observed(
+            &|sol| {
+                if keep(&sol) {
+                    println!("{sol}");
+                }
+            },
+            &observer,
+        ),
+    };
+
+    if args.shard.is_some() {
+        println!("{SHARD_COMPLETE}");
+    }
+    if args.stats {
+        eprint!("{stats}");
     }
 }
@@ -5,8 +5,9 @@ use std::{
 
 use ascii::{AsciiChar, AsciiString};
 use square_word::{
+    Solution, SolutionSet,
     finder::{Puzzle, PuzzleViewModel, RowHint},
-    Solution,
+    read_solutions,
 };
 
 fn read_line(stdin: &Stdin) -> Result<String, io::Error> {
@@ -23,16 +24,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("What's in your grid?");
     println!("Use '.', '_', or ' ' for empty spaces");
     let grids = (0..5)
-        .map(|i| loop {
-            match read_line(&stdin) {
-                Ok(line) => {
-                    if line.len() == 5 {
-                        return line;
-                    } else {
-                        println!("line must be 5 long, please enter line {} again", i + 1);
+        .map(|i| {
+            loop {
+                match read_line(&stdin) {
+                    Ok(line) => {
+                        if line.len() == 5 {
+                            return line;
+                        } else {
+                            println!("line must be 5 long, please enter line {} again", i + 1);
+                        }
                     }
+                    Err(_) => continue,
                 }
-                Err(_) => continue,
             }
         })
         .map(|s| {
@@ -56,18 +59,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let hints: [_; 5] = hints.try_into().unwrap();
 
     let lines = include_str!("../../solutions.txt");
-    let solutions = lines
-        .lines()
-        .map(|line| {
-            Solution::new(
-                line.split(',')
-                    .map(|word| AsciiString::from_ascii(word).unwrap())
-                    .collect::<Vec<_>>()
-                    .try_into()
-                    .unwrap(),
-            )
-        })
-        .collect::<Vec<_>>();
+    let solutions: Vec<Solution> = read_solutions(lines, SolutionSet::Expanded).unwrap();
 
     let vm = PuzzleViewModel {
         guesses: vec![guess.clone().into()],
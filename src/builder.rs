@@ -11,7 +11,7 @@ impl Display for BuildError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let words = match self {
             BuildError::Incomplete => {
-                "Not enough words have been added to this builder, 5 are needed"
+                "Not enough words have been added to this builder, it needs one for every row"
             }
         };
         writeln!(f, "{words}")
@@ -21,9 +21,9 @@ impl Display for BuildError {
 impl Error for BuildError {}
 
 #[derive(Debug, PartialEq, Eq)]
-pub enum AddedWord {
+pub enum AddedWord<const N: usize = 5> {
     Incomplete,
-    Finished(Box<[Solution; 2]>),
+    Finished(Box<[Solution<N>; 2]>),
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -44,7 +44,7 @@ impl Display for AddError {
                 "There are no possible valid solutions if this words were to be added"
             }
             AddError::FinishedDuplicate => "By finishing this, a duplicate would be created",
-            AddError::TooManyRows => "More than 5 rows have been added",
+            AddError::TooManyRows => "More rows have been added than the grid has",
         };
         writeln!(f, "{words}")
     }
@@ -68,12 +68,13 @@ impl Display for RemoveError {
 
 impl Error for RemoveError {}
 
-pub struct SolutionBuilder<'a> {
+/// Fills an `N`×`N` grid a row at a time, checking the columns can still be words as it goes
+pub struct SolutionBuilder<'a, const N: usize = 5> {
     pub words: Vec<&'a str>,
     possible_columns: &'a WordList,
 }
 
-impl<'a> SolutionBuilder<'a> {
+impl<'a, const N: usize> SolutionBuilder<'a, N> {
     pub fn new(columns: &'a WordList) -> Self {
         Self {
             words: Vec::new(),
@@ -81,11 +82,11 @@ impl<'a> SolutionBuilder<'a> {
         }
     }
 
-    pub fn add(&mut self, word: &'a str) -> Result<AddedWord, AddError> {
+    pub fn add(&mut self, word: &'a str) -> Result<AddedWord<N>, AddError> {
         if self.words.contains(&word) {
             Err(AddError::Duplicate)
         } else {
-            if self.words.len() >= 5 {
+            if self.words.len() >= N {
                 return Err(AddError::TooManyRows);
             }
             self.words.push(word);
@@ -102,11 +103,11 @@ impl<'a> SolutionBuilder<'a> {
             {
                 self.pop().unwrap();
                 Err(AddError::InvalidColumns)
-            } else if self.words.len() == 5 {
+            } else if self.words.len() == N {
                 let words = [self.words.iter().map(|s| s.to_string()).collect(), columns].concat();
                 let set: HashSet<&String> = HashSet::from_iter(words.iter());
 
-                if set.len() == 10 {
+                if set.len() == 2 * N {
                     Ok(AddedWord::Finished(Box::new(self.build().unwrap())))
                 } else {
                     Err(AddError::FinishedDuplicate)
@@ -126,8 +127,8 @@ impl<'a> SolutionBuilder<'a> {
         }
     }
 
-    fn build(&self) -> Result<[Solution; 2], BuildError> {
-        if self.words.len() == 5 {
+    fn build(&self) -> Result<[Solution<N>; 2], BuildError> {
+        if self.words.len() == N {
             Ok([
                 Solution::new(self.words.clone().try_into().unwrap()),
                 Solution::new(self.columns().try_into().unwrap()),
@@ -138,7 +139,7 @@ impl<'a> SolutionBuilder<'a> {
     }
 
     fn columns(&self) -> Vec<String> {
-        Vec::from_iter((0..5).map(|i| {
+        Vec::from_iter((0..N).map(|i| {
             self.words
                 .iter()
                 .map(|row| row.as_bytes()[i] as char)
@@ -163,7 +164,7 @@ mod test {
     #[test]
     fn adding_five_letter_word_works() {
         let wordlist = sample_wordlist();
-        let mut builder = SolutionBuilder::new(&wordlist);
+        let mut builder = SolutionBuilder::<5>::new(&wordlist);
         let actual = builder.add(ROWS[0]);
         let expected = Ok(AddedWord::Incomplete);
         assert_eq!(actual, expected);
@@ -172,7 +173,7 @@ mod test {
     #[test]
     fn when_the_same_word_is_added_twice_returns_a_duplicate_error() {
         let wordlist = sample_wordlist();
-        let mut builder = SolutionBuilder::new(&wordlist);
+        let mut builder = SolutionBuilder::<5>::new(&wordlist);
         builder.add(ROWS[0]).unwrap();
         let actual = builder.add(ROWS[0]);
         let expected = Err(AddError::Duplicate);
@@ -182,7 +183,7 @@ mod test {
     #[test]
     fn adding_a_word_out_of_alphabetical_order_returns_error() {
         let wordlist = sample_wordlist();
-        let mut builder = SolutionBuilder::new(&wordlist);
+        let mut builder = SolutionBuilder::<5>::new(&wordlist);
         builder.add(COLUMNS[0]).unwrap();
         let actual = builder.add(COLUMNS[1]);
         let expected = Err(AddError::WrongOrder);
@@ -192,7 +193,7 @@ mod test {
     #[test]
     fn adding_a_word_in_the_correct_order_is_fine() {
         let wordlist = sample_wordlist();
-        let mut builder = SolutionBuilder::new(&wordlist);
+        let mut builder = SolutionBuilder::<5>::new(&wordlist);
         builder.add(ROWS[0]).unwrap();
         let actual = builder.add(ROWS[1]);
         let expected = Ok(AddedWord::Incomplete);
@@ -202,7 +203,7 @@ mod test {
     #[test]
     fn a_word_not_in_the_possible_rows_is_not_possible() {
         let possible_columns = sample_wordlist();
-        let mut builder = SolutionBuilder::new(&possible_columns);
+        let mut builder = SolutionBuilder::<5>::new(&possible_columns);
         let actual = builder.add("dummy");
         let expected = Err(AddError::InvalidColumns);
         assert_eq!(actual, expected);
@@ -227,7 +228,7 @@ mod test {
     #[test]
     fn pop_on_empty_returns_error() {
         let wordlist = WordList::new(vec![]);
-        let mut builder = SolutionBuilder::new(&wordlist);
+        let mut builder = SolutionBuilder::<5>::new(&wordlist);
         let actual = builder.pop();
         let expected = Err(RemoveError::AlreadyEmpty);
         assert_eq!(actual, expected);
@@ -236,7 +237,7 @@ mod test {
     #[test]
     fn popping_from_non_empty_is_ok() {
         let wordlist = sample_wordlist();
-        let mut builder = SolutionBuilder::new(&wordlist);
+        let mut builder = SolutionBuilder::<5>::new(&wordlist);
         builder.add(ROWS[0]).unwrap();
         let actual = builder.pop();
         let expected = Ok(());
@@ -251,7 +252,7 @@ mod test {
                 .split(',')
                 .collect();
             let list = WordList::new(words);
-            let mut builder = SolutionBuilder::new(&list);
+            let mut builder = SolutionBuilder::<5>::new(&list);
 
             builder.add("which")?;
             builder.add("hydra")?;
@@ -270,7 +271,7 @@ mod test {
     #[test]
     fn adding_to_a_full_solution_gives_an_error() {
         let possible_columns = WordList::new(Vec::from(COLUMNS));
-        let mut builder = SolutionBuilder::new(&possible_columns);
+        let mut builder = SolutionBuilder::<5>::new(&possible_columns);
         builder.add(ROWS[0]).unwrap();
         builder.add(ROWS[1]).unwrap();
         builder.add(ROWS[2]).unwrap();
@@ -0,0 +1,176 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Stops a search early, either from another thread or once it passes a deadline or has placed
+/// a number of words.
+///
+/// One token can be shared by every thread of a search, and by several searches, which then
+/// share its node budget.
+#[derive(Debug, Default)]
+pub struct CancelToken {
+    cancelled: AtomicBool,
+    deadline: Option<Instant>,
+    node_budget: Option<u64>,
+    nodes: AtomicU64,
+}
+
+impl CancelToken {
+    /// A token that only stops a search when [`cancel`](Self::cancel) is called
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    /// Stops the search `timeout` from now
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Stops the search once it has placed about `nodes` words. Searches only check in every
+    /// [`CHECK_EVERY`] words, so each thread may go a little over.
+    pub fn with_node_budget(self, nodes: u64) -> Self {
+        Self {
+            node_budget: Some(nodes),
+            ..self
+        }
+    }
+
+    /// Stops every search using this token the next time it checks in
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Adds `nodes` to the words placed so far and says whether the search should stop,
+    /// cancelling the token for everything else using it if the deadline or budget ran out
+    pub fn charge(&self, nodes: u64) -> bool {
+        let total = self.nodes.fetch_add(nodes, Ordering::Relaxed) + nodes;
+        let out_of_nodes = self.node_budget.is_some_and(|budget| total >= budget);
+        let out_of_time = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if out_of_nodes || out_of_time {
+            self.cancel();
+        }
+        self.is_cancelled()
+    }
+}
+
+/// How many words a search places between checking in with its [`CancelToken`]
+pub const CHECK_EVERY: u64 = 256;
+
+/// Lets one thread of a search check its token without touching shared state for every word
+#[derive(Debug)]
+pub(crate) struct NodeMeter<'t> {
+    token: &'t CancelToken,
+    unreported: u64,
+    stopped: bool,
+}
+
+impl<'t> NodeMeter<'t> {
+    pub fn new(token: &'t CancelToken) -> Self {
+        Self {
+            token,
+            unreported: 0,
+            stopped: token.is_cancelled(),
+        }
+    }
+
+    /// Counts one more word placed and says whether the search should stop
+    pub fn tick(&mut self) -> bool {
+        self.unreported += 1;
+        if !self.stopped && self.unreported >= CHECK_EVERY {
+            self.stopped = self.token.charge(self.unreported);
+            self.unreported = 0;
+        }
+        self.stopped
+    }
+
+    /// Whether the search should stop, without counting anything
+    pub fn stopped(&self) -> bool {
+        self.stopped
+    }
+}
+
+impl Drop for NodeMeter<'_> {
+    /// Hands on the words placed since the last check, so short searches still use up the budget
+    fn drop(&mut self) {
+        if self.unreported > 0 {
+            self.token.charge(self.unreported);
+        }
+    }
+}
+
+/// What a search found, and whether it got to the end or was stopped first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome<T> {
+    pub found: T,
+    pub complete: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn a_token_only_stops_once_cancelled() {
+        let token = CancelToken::new();
+
+        assert!(!token.charge(1_000_000));
+
+        token.cancel();
+        assert!(token.charge(0));
+    }
+
+    #[test]
+    fn a_token_stops_when_the_budget_runs_out() {
+        let token = CancelToken::new().with_node_budget(1_000);
+
+        assert!(!token.charge(600));
+        assert!(token.charge(600));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn a_token_stops_after_its_deadline() {
+        let token = CancelToken::new().with_deadline(Instant::now());
+
+        assert!(token.charge(0));
+    }
+
+    #[test]
+    fn meters_check_in_every_so_often() {
+        let token = CancelToken::new().with_node_budget(CHECK_EVERY);
+        let mut meter = NodeMeter::new(&token);
+
+        for _ in 1..CHECK_EVERY {
+            assert!(!meter.tick());
+        }
+        assert!(meter.tick());
+        assert!(NodeMeter::new(&token).stopped());
+    }
+
+    #[test]
+    fn meters_hand_on_what_is_left_when_dropped() {
+        let token = CancelToken::new().with_node_budget(CHECK_EVERY + 2);
+        for _ in 0..2 {
+            let mut meter = NodeMeter::new(&token);
+            for _ in 0..CHECK_EVERY / 2 + 1 {
+                meter.tick();
+            }
+        }
+
+        assert!(token.is_cancelled());
+    }
+}
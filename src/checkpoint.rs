@@ -0,0 +1,303 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{Solution, shard::Fingerprint};
+
+/// The first-row words whose search branches are finished, and every solution they produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint<const N: usize = 5> {
+    /// The search this is part of, so it's never picked up by a different one
+    settings: Fingerprint,
+    finished: BTreeSet<String>,
+    solutions: Vec<Solution<N>>,
+}
+
+impl<const N: usize> Checkpoint<N> {
+    /// A checkpoint with nothing finished yet, for the search with these `settings`
+    pub fn new(settings: Fingerprint) -> Self {
+        Self {
+            settings,
+            finished: BTreeSet::new(),
+            solutions: Vec::new(),
+        }
+    }
+
+    /// Reads a checkpoint from `path`, starting from an empty one if the file doesn't exist yet.
+    /// Fails if the file was saved by a search with different `settings`.
+    pub fn load(path: &Path, settings: Fingerprint) -> Result<Self, io::Error> {
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        let checkpoint: Self = match fs::read_to_string(path) {
+            Ok(contents) => contents.parse().map_err(invalid)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new(settings)),
+            Err(e) => return Err(e),
+        };
+        if checkpoint.settings != settings {
+            return Err(invalid(format!(
+                "{} was saved by a search with settings {}, not {settings}",
+                path.display(),
+                checkpoint.settings
+            )));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Writes to a temporary file first so an interrupted save never clobbers the last good one
+    pub fn save(&self, path: &Path) -> Result<(), io::Error> {
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        fs::write(&temp, self.to_string())?;
+        fs::rename(&temp, path)
+    }
+
+    pub fn is_finished(&self, first_row: &str) -> bool {
+        self.finished.contains(first_row)
+    }
+
+    pub fn finish(&mut self, first_row: &str, mut solutions: Vec<Solution<N>>) {
+        if self.finished.insert(first_row.to_string()) {
+            self.solutions.append(&mut solutions);
+        }
+    }
+
+    pub fn finished_count(&self) -> usize {
+        self.finished.len()
+    }
+
+    pub fn solutions(&self) -> &[Solution<N>] {
+        &self.solutions
+    }
+}
+
+impl<const N: usize> Display for Checkpoint<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "settings {}", self.settings)?;
+        for word in self.finished.iter() {
+            writeln!(f, "finished {word}")?;
+        }
+        for sol in self.solutions.iter() {
+            writeln!(f, "solution {sol}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> FromStr for Checkpoint<N> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate();
+        let settings = lines
+            .next()
+            .and_then(|(_, line)| line.strip_prefix("settings "))
+            .ok_or("line 1: expected the search's settings")?;
+        let mut checkpoint = Self::new(settings.parse()?);
+        for (number, line) in lines {
+            match line.split_once(' ') {
+                Some(("finished", word)) => {
+                    checkpoint.finished.insert(word.to_string());
+                }
+                Some(("solution", sol)) => {
+                    let sol = sol
+                        .parse()
+                        .map_err(|_| format!("line {}: invalid solution {sol:?}", number + 1))?;
+                    checkpoint.solutions.push(sol);
+                }
+                _ if line.is_empty() => {}
+                _ => return Err(format!("line {}: unexpected {line:?}", number + 1)),
+            }
+        }
+        Ok(checkpoint)
+    }
+}
+
+/// Shares a [`Checkpoint`] between search threads and saves it to disk every `interval`.
+pub struct Checkpointer<const N: usize = 5> {
+    path: PathBuf,
+    interval: Duration,
+    state: Mutex<(Checkpoint<N>, Instant)>,
+}
+
+impl<const N: usize> Checkpointer<N> {
+    /// Picks up the checkpoint at `path` for the search with these `settings`, see
+    /// [`Checkpoint::load`]
+    pub fn open(
+        path: impl Into<PathBuf>,
+        settings: Fingerprint,
+        interval: Duration,
+    ) -> Result<Self, io::Error> {
+        let path = path.into();
+        let checkpoint = Checkpoint::load(&path, settings)?;
+        Ok(Self {
+            path,
+            interval,
+            state: Mutex::new((checkpoint, Instant::now())),
+        })
+    }
+
+    pub fn is_finished(&self, first_row: &str) -> bool {
+        self.state.lock().unwrap().0.is_finished(first_row)
+    }
+
+    pub fn finished_count(&self) -> usize {
+        self.state.lock().unwrap().0.finished_count()
+    }
+
+    /// Records a finished branch, saving if the last save was more than `interval` ago
+    pub fn finish(&self, first_row: &str, solutions: Vec<Solution<N>>) {
+        let mut state = self.state.lock().unwrap();
+        let (checkpoint, last_saved) = &mut *state;
+        checkpoint.finish(first_row, solutions);
+        if last_saved.elapsed() >= self.interval {
+            match checkpoint.save(&self.path) {
+                Ok(()) => *last_saved = Instant::now(),
+                Err(e) => eprintln!("Couldn't save checkpoint to {}: {e}", self.path.display()),
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), io::Error> {
+        let mut state = self.state.lock().unwrap();
+        state.0.save(&self.path)?;
+        state.1 = Instant::now();
+        Ok(())
+    }
+
+    /// Saves a final checkpoint and exits when the process gets Ctrl-C
+    pub fn save_on_interrupt(self: &Arc<Self>) -> Result<(), ctrlc::Error> {
+        let checkpointer = Arc::clone(self);
+        ctrlc::set_handler(move || {
+            match checkpointer.save() {
+                Ok(()) => eprintln!(
+                    "Interrupted, saved {} finished branches to {}",
+                    checkpointer.finished_count(),
+                    checkpointer.path.display()
+                ),
+                Err(e) => eprintln!("Interrupted, but couldn't save checkpoint: {e}"),
+            }
+            std::process::exit(130);
+        })
+    }
+
+    pub fn solutions(&self) -> Vec<Solution<N>> {
+        self.state.lock().unwrap().0.solutions().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::finder::new_double_sided::Constraints;
+    use pretty_assertions::assert_eq;
+
+    fn settings() -> Fingerprint {
+        Fingerprint::of("grime honor outdo")
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut checkpoint = Checkpoint::new(settings());
+        checkpoint.finish(
+            "grime",
+            vec![Solution::new(["grime", "honor", "outdo", "steed", "terse"])],
+        );
+        checkpoint.finish("honor", vec![]);
+
+        let actual: Checkpoint = checkpoint.to_string().parse().unwrap();
+
+        assert_eq!(actual, checkpoint);
+    }
+
+    #[test]
+    fn finishing_a_branch_twice_keeps_one_copy_of_its_solutions() {
+        let sol = Solution::new(["grime", "honor", "outdo", "steed", "terse"]);
+        let mut checkpoint = Checkpoint::new(settings());
+        checkpoint.finish("grime", vec![sol.clone()]);
+        checkpoint.finish("grime", vec![sol.clone()]);
+
+        assert_eq!(checkpoint.solutions(), &[sol]);
+    }
+
+    #[test]
+    fn rejects_unknown_lines() {
+        let actual = format!("settings {}\nfinished grime\nbogus line", settings());
+
+        assert!(actual.parse::<Checkpoint>().is_err());
+        assert!("finished grime".parse::<Checkpoint>().is_err());
+    }
+
+    #[test]
+    fn only_resumes_the_search_that_saved_it() {
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.txt", std::process::id()));
+        let mut checkpoint = Checkpoint::<5>::new(settings());
+        checkpoint.finish("grime", vec![]);
+        checkpoint.save(&path).unwrap();
+
+        let same = Checkpoint::load(&path, settings());
+        let other = Checkpoint::<5>::load(&path, Fingerprint::of("grime honor"));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(same.unwrap(), checkpoint);
+        assert_eq!(other.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn resuming_skips_finished_branches_and_keeps_their_solutions() {
+        let words = vec![
+            "grime", "honor", "outdo", "steed", "terse", "ghost", "route", "inter", "modes",
+            "erode",
+        ];
+        let everything = crate::finder::new_double_sided::solutions(&words);
+
+        let first_run = Mutex::new(Checkpoint::<5>::new(settings()));
+        crate::finder::new_double_sided::solutions_by_first_row(
+            &words,
+            &Constraints::default(),
+            &|word| word > "honor",
+            &|word, sols| {
+                let sols = sols.into_iter().map(Solution::new).collect();
+                first_run.lock().unwrap().finish(word, sols)
+            },
+            &|_| {},
+        );
+        let first_run = first_run.into_inner().unwrap();
+
+        let resumed = Mutex::new(first_run.clone());
+        let searched = Mutex::new(vec![]);
+        let last_progress = Mutex::new(None);
+        crate::finder::new_double_sided::solutions_by_first_row(
+            &words,
+            &Constraints::default(),
+            &|word| resumed.lock().unwrap().is_finished(word),
+            &|word, sols| {
+                searched.lock().unwrap().push(word.to_string());
+                let sols = sols.into_iter().map(Solution::new).collect();
+                resumed.lock().unwrap().finish(word, sols)
+            },
+            &|progress| *last_progress.lock().unwrap() = Some(progress),
+        );
+        let resumed = resumed.into_inner().unwrap();
+
+        assert!(
+            searched
+                .into_inner()
+                .unwrap()
+                .iter()
+                .all(|word| !first_run.is_finished(word))
+        );
+        let last_progress = last_progress.into_inner().unwrap().unwrap();
+        assert_eq!(
+            last_progress.branches,
+            words.len() - first_run.finished_count()
+        );
+        assert_eq!(last_progress.branches_done, last_progress.branches);
+        assert_eq!(resumed.finished_count(), words.len());
+        assert_eq!(resumed.solutions().len(), everything.len());
+    }
+}
@@ -0,0 +1,826 @@
+use std::{ops::RangeInclusive, path::PathBuf};
+
+use ascii::AsciiChar;
+
+use crate::{
+    DuplicatePolicy,
+    commonness::{Commonness, ScoreBy},
+    finder::FinderKind,
+    rules::{LetterRules, WordRules},
+    selfplay::StrategyKind,
+    shard::{Fingerprint, Shard},
+    template::Template,
+};
+
+/// Options for the `solve` binary
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SolveArgs {
+    /// Which engine to search with
+    pub finder: FinderKind,
+    /// Only search the first this many words of the word list
+    pub word_limit: Option<usize>,
+    /// How many letters across the grid is, five unless one was given
+    pub size: Option<usize>,
+    /// File to read the word list from, `words.txt` unless one was given
+    pub words: Option<PathBuf>,
+    /// State file to resume from and keep saving progress to
+    pub resume: Option<PathBuf>,
+    /// Print how many nodes the search visited and how many each check pruned
+    pub stats: bool,
+    /// Print how many solutions each first row leads to instead of the solutions themselves
+    pub count: bool,
+    /// Letters every solution has to have, blank unless one was given
+    pub template: Template,
+    /// Which grids with repeated words count as solutions
+    pub duplicates: DuplicatePolicy,
+    /// Only search this part of the first rows, so a run can be split across machines
+    pub shard: Option<Shard>,
+    /// Only print the canonical form of each solution rather than it and its transpose
+    pub canonical: bool,
+    /// Words every solution has to have, and words none of them can use
+    pub rules: WordRules,
+    /// How often letters can repeat, and letters every solution has to have or can't have
+    pub letters: LetterRules,
+    /// File of word scores, ranking words by their order in the word list unless one was given
+    pub scores: Option<PathBuf>,
+    /// Which part of each solution's score `min_score` applies to
+    pub score_by: ScoreBy,
+    /// Only print solutions scoring at least this
+    pub min_score: Option<f64>,
+}
+
+/// The grid sizes `solve` can search, which are the ones the game can be played at
+pub const SIZES: RangeInclusive<usize> = 3..=7;
+
+pub const SOLVE_USAGE: &str = "usage: solve [word-limit] [--size <n>] [--words <file>] \
+    [--finder <name>] [--resume <state-file>] [--stats] [--count] [--template <rows>] \
+    [--duplicates <distinct|symmetric|any>] [--shard <i/n>] [--canonical] \
+    [--require <word>[@row<n>|@column<n>]]... [--forbid <word>]... \
+    [--max-repeats <n>] [--ban-letters <letters>] [--require-letters <letters>] \
+    [--scores <file>] [--score-by <min|mean>] [--min-score <score>]";
+
+impl SolveArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--finder" => {
+                    let name = args.next().ok_or("--finder needs the name of a finder")?;
+                    parsed.finder = name.parse()?;
+                }
+                "--size" => {
+                    let size = args.next().ok_or("--size needs a number")?;
+                    let size = size
+                        .parse()
+                        .ok()
+                        .filter(|size| SIZES.contains(size))
+                        .ok_or_else(|| {
+                            format!(
+                                "--size needs a number from {} to {}, got {size:?}",
+                                SIZES.start(),
+                                SIZES.end()
+                            )
+                        })?;
+                    parsed.size = Some(size);
+                }
+                "--words" => {
+                    let path = args.next().ok_or("--words needs a file")?;
+                    parsed.words = Some(path.into());
+                }
+                "--resume" => {
+                    let path = args.next().ok_or("--resume needs a state file")?;
+                    parsed.resume = Some(path.into());
+                }
+                "--stats" => parsed.stats = true,
+                "--count" => parsed.count = true,
+                "--canonical" => parsed.canonical = true,
+                "--template" => {
+                    let rows = args
+                        .next()
+                        .ok_or("--template needs five rows like g????/...")?;
+                    parsed.template = rows.parse()?;
+                }
+                "--duplicates" => {
+                    let policy = args
+                        .next()
+                        .ok_or("--duplicates needs distinct, symmetric or any")?;
+                    parsed.duplicates = policy.parse()?;
+                }
+                "--shard" => {
+                    let shard = args.next().ok_or("--shard needs a shard like 2/4")?;
+                    parsed.shard = Some(shard.parse()?);
+                }
+                "--require" => {
+                    let word = args
+                        .next()
+                        .ok_or("--require needs a word like theme or theme@row2")?;
+                    parsed.rules.require(word.parse()?);
+                }
+                "--forbid" => {
+                    let word = args.next().ok_or("--forbid needs a word")?;
+                    parsed.rules.forbid(word);
+                }
+                "--max-repeats" => {
+                    let max = args.next().ok_or("--max-repeats needs a number")?;
+                    let max = max
+                        .parse()
+                        .map_err(|_| format!("--max-repeats needs a number, got {max:?}"))?;
+                    parsed.letters.limit_repeats(max);
+                }
+                "--ban-letters" => {
+                    let letters = args.next().ok_or("--ban-letters needs some letters")?;
+                    for letter in parse_letters(&letters)? {
+                        parsed.letters.ban(letter);
+                    }
+                }
+                "--require-letters" => {
+                    let letters = args.next().ok_or("--require-letters needs some letters")?;
+                    for letter in parse_letters(&letters)? {
+                        parsed.letters.require(letter);
+                    }
+                }
+                "--scores" => {
+                    let path = args.next().ok_or("--scores needs a file")?;
+                    parsed.scores = Some(path.into());
+                }
+                "--score-by" => {
+                    let by = args.next().ok_or("--score-by needs min or mean")?;
+                    parsed.score_by = by.parse()?;
+                }
+                "--min-score" => {
+                    let score = args.next().ok_or("--min-score needs a score")?;
+                    parsed.min_score = Some(parse_score(&score)?);
+                }
+                _ => {
+                    let limit = arg
+                        .parse()
+                        .map_err(|_| format!("unexpected argument {arg:?}"))?;
+                    parsed.word_limit = Some(limit);
+                }
+            }
+        }
+        if parsed.count && parsed.resume.is_some() {
+            return Err("--count doesn't save progress, so it can't --resume".to_string());
+        }
+        if parsed.count && parsed.min_score.is_some() {
+            return Err(
+                "--count doesn't score the solutions, so it can't use --min-score".to_string(),
+            );
+        }
+        if parsed.size() != 5 && !parsed.template.is_blank() {
+            return Err("--template only works with 5 by 5 grids".to_string());
+        }
+        parsed.rules.check(parsed.size())?;
+        Ok(parsed)
+    }
+
+    /// Parses the process arguments, exiting with the usage message if they're invalid
+    pub fn from_env() -> Self {
+        parse_env(Self::parse, SOLVE_USAGE)
+    }
+
+    /// How many letters across the grid is
+    pub fn size(&self) -> usize {
+        self.size.unwrap_or(5)
+    }
+
+    /// Identifies the solutions a search of `words` with these options prints, so the shards of
+    /// one search can be told apart from another's. `scores` are the ones `--scores` loaded,
+    /// which only matter with a `--min-score`.
+    pub fn fingerprint(&self, words: &[&str], scores: &Commonness) -> Fingerprint {
+        let min_score = match self.min_score {
+            Some(min) => format!("{min} by {} from\n{scores}", self.score_by),
+            None => "none".to_string(),
+        };
+        Fingerprint::of(format_args!(
+            "words {}\ntemplate {}\nduplicates {}\ncanonical {}\nrules {}\nletters {}\n\
+             min score {min_score}",
+            words.join(" "),
+            self.template,
+            self.duplicates,
+            self.canonical,
+            self.rules,
+            self.letters,
+        ))
+    }
+
+    /// Identifies the search a `--resume` checkpoint belongs to. On top of the
+    /// [`fingerprint`](Self::fingerprint) that's the finder and the shard, since a checkpoint
+    /// only holds the first rows of its own shard, in the order its finder takes them.
+    pub fn checkpoint_fingerprint(&self, words: &[&str], scores: &Commonness) -> Fingerprint {
+        Fingerprint::of(format_args!(
+            "{}\nfinder {}\nshard {}",
+            self.fingerprint(words, scores),
+            self.finder,
+            self.shard.unwrap_or_default()
+        ))
+    }
+}
+
+/// Options for the `estimate` binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EstimateArgs {
+    /// Only estimate a search of the first this many words of the word list
+    pub word_limit: Option<usize>,
+    /// Random paths to follow down the search tree
+    pub samples: usize,
+    /// Seed for picking the paths, so an estimate can be repeated
+    pub seed: Option<u64>,
+}
+
+impl Default for EstimateArgs {
+    fn default() -> Self {
+        Self {
+            word_limit: None,
+            samples: 10_000,
+            seed: None,
+        }
+    }
+}
+
+pub const ESTIMATE_USAGE: &str = "usage: estimate [word-limit] [--samples <n>] [--seed <n>]";
+
+impl EstimateArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--samples" => {
+                    let samples = args.next().ok_or("--samples needs a number")?;
+                    parsed.samples = samples
+                        .parse()
+                        .map_err(|_| format!("--samples needs a number, got {samples:?}"))?;
+                }
+                "--seed" => {
+                    let seed = args.next().ok_or("--seed needs a number")?;
+                    let seed = seed
+                        .parse()
+                        .map_err(|_| format!("--seed needs a number, got {seed:?}"))?;
+                    parsed.seed = Some(seed);
+                }
+                _ => {
+                    let limit = arg
+                        .parse()
+                        .map_err(|_| format!("unexpected argument {arg:?}"))?;
+                    parsed.word_limit = Some(limit);
+                }
+            }
+        }
+        if parsed.samples == 0 {
+            return Err("--samples has to be at least 1".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Parses the process arguments, exiting with the usage message if they're invalid
+    pub fn from_env() -> Self {
+        parse_env(Self::parse, ESTIMATE_USAGE)
+    }
+}
+
+/// Options for the `merge` binary
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeArgs {
+    /// Where to write the merged solutions, stdout unless one was given
+    pub output: Option<PathBuf>,
+    /// The output of every shard of one search
+    pub shard_files: Vec<PathBuf>,
+}
+
+pub const MERGE_USAGE: &str = "usage: merge [--output <file>] <shard-file>...";
+
+impl MergeArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--output" => {
+                    let path = args.next().ok_or("--output needs a file")?;
+                    parsed.output = Some(path.into());
+                }
+                _ => parsed.shard_files.push(arg.into()),
+            }
+        }
+        if parsed.shard_files.is_empty() {
+            return Err("merge needs at least one shard file".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Parses the process arguments, exiting with the usage message if they're invalid
+    pub fn from_env() -> Self {
+        parse_env(Self::parse, MERGE_USAGE)
+    }
+}
+
+/// Options for the `rank` binary
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankArgs {
+    /// The solutions to rank
+    pub solutions: PathBuf,
+    /// File of word scores, ranking words by their order in the word list unless one was given
+    pub scores: Option<PathBuf>,
+    /// Which part of each solution's score to sort and filter by
+    pub score_by: ScoreBy,
+    /// Only print solutions scoring at least this
+    pub min_score: Option<f64>,
+    /// Only print this many of the best solutions
+    pub top: Option<usize>,
+    /// Print each solution's score after it
+    pub show_scores: bool,
+    /// Only rank the canonical form of each solution rather than it and its transpose
+    pub canonical: bool,
+}
+
+impl Default for RankArgs {
+    fn default() -> Self {
+        Self {
+            solutions: "solutions.txt".into(),
+            scores: None,
+            score_by: ScoreBy::default(),
+            min_score: None,
+            top: None,
+            show_scores: false,
+            canonical: false,
+        }
+    }
+}
+
+pub const RANK_USAGE: &str = "usage: rank [solutions-file] [--scores <file>] \
+    [--score-by <min|mean>] [--min-score <score>] [--top <n>] [--show-scores] [--canonical]";
+
+impl RankArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--scores" => {
+                    let path = args.next().ok_or("--scores needs a file")?;
+                    parsed.scores = Some(path.into());
+                }
+                "--score-by" => {
+                    let by = args.next().ok_or("--score-by needs min or mean")?;
+                    parsed.score_by = by.parse()?;
+                }
+                "--min-score" => {
+                    let score = args.next().ok_or("--min-score needs a score")?;
+                    parsed.min_score = Some(parse_score(&score)?);
+                }
+                "--top" => {
+                    let top = args.next().ok_or("--top needs a number")?;
+                    let top = top
+                        .parse()
+                        .map_err(|_| format!("--top needs a number, got {top:?}"))?;
+                    parsed.top = Some(top);
+                }
+                "--show-scores" => parsed.show_scores = true,
+                "--canonical" => parsed.canonical = true,
+                _ => parsed.solutions = arg.into(),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Parses the process arguments, exiting with the usage message if they're invalid
+    pub fn from_env() -> Self {
+        parse_env(Self::parse, RANK_USAGE)
+    }
+}
+
+/// Options for the `selfplay` binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfplayArgs {
+    /// The puzzles to play
+    pub solutions: PathBuf,
+    pub strategy: StrategyKind,
+    /// Only play this many puzzles picked at random
+    pub sample: Option<usize>,
+    /// Seed for picking the sample, so a run can be repeated
+    pub seed: Option<u64>,
+    /// Give up on a puzzle after this many guesses
+    pub max_guesses: usize,
+    /// How many of the worst and slowest games to list
+    pub shown: usize,
+}
+
+impl Default for SelfplayArgs {
+    fn default() -> Self {
+        Self {
+            solutions: "solutions.txt".into(),
+            strategy: StrategyKind::default(),
+            sample: None,
+            seed: None,
+            max_guesses: 20,
+            shown: 5,
+        }
+    }
+}
+
+pub const SELFPLAY_USAGE: &str = "usage: selfplay [solutions-file] \
+    [--strategy <entropy|first-candidate>] [--sample <n>] [--seed <n>] [--max-guesses <n>] \
+    [--show <n>]";
+
+impl SelfplayArgs {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        let number = |option: &str, args: &mut dyn Iterator<Item = String>| {
+            let number = args
+                .next()
+                .ok_or_else(|| format!("{option} needs a number"))?;
+            number
+                .parse::<usize>()
+                .map_err(|_| format!("{option} needs a number, got {number:?}"))
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--strategy" => {
+                    let strategy = args.next().ok_or("--strategy needs a name")?;
+                    parsed.strategy = strategy.parse()?;
+                }
+                "--sample" => parsed.sample = Some(number("--sample", &mut args)?),
+                "--seed" => {
+                    let seed = args.next().ok_or("--seed needs a number")?;
+                    let seed = seed
+                        .parse()
+                        .map_err(|_| format!("--seed needs a number, got {seed:?}"))?;
+                    parsed.seed = Some(seed);
+                }
+                "--max-guesses" => parsed.max_guesses = number("--max-guesses", &mut args)?,
+                "--show" => parsed.shown = number("--show", &mut args)?,
+                _ => parsed.solutions = arg.into(),
+            }
+        }
+        if parsed.max_guesses == 0 {
+            return Err("--max-guesses has to be at least 1".to_string());
+        }
+        Ok(parsed)
+    }
+
+    /// Parses the process arguments, exiting with the usage message if they're invalid
+    pub fn from_env() -> Self {
+        parse_env(Self::parse, SELFPLAY_USAGE)
+    }
+}
+
+fn parse_score(score: &str) -> Result<f64, String> {
+    score
+        .parse::<f64>()
+        .ok()
+        .filter(|score| score.is_finite())
+        .ok_or_else(|| format!("--min-score needs a number, got {score:?}"))
+}
+
+/// Reads a run of lowercase letters like `qxz`
+fn parse_letters(letters: &str) -> Result<Vec<AsciiChar>, String> {
+    letters
+        .chars()
+        .map(|ch| match ch {
+            'a'..='z' => Ok(AsciiChar::from_ascii(ch).unwrap()),
+            _ => Err(format!(
+                "expected lowercase letters, got {ch:?} in {letters:?}"
+            )),
+        })
+        .collect()
+}
+
+fn parse_env<T>(
+    parse: impl FnOnce(std::iter::Skip<std::env::Args>) -> Result<T, String>,
+    usage: &str,
+) -> T {
+    match parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{usage}");
+            std::process::exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<SolveArgs, String> {
+        SolveArgs::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_searches_everything() {
+        assert_eq!(parse(&[]), Ok(SolveArgs::default()));
+    }
+
+    #[test]
+    fn reads_a_word_limit_and_state_file() {
+        let expected = SolveArgs {
+            word_limit: Some(300),
+            resume: Some("state.txt".into()),
+            ..Default::default()
+        };
+
+        assert_eq!(parse(&["300", "--resume", "state.txt"]), Ok(expected));
+    }
+
+    #[test]
+    fn reads_the_stats_flag_anywhere() {
+        let actual = parse(&["--stats", "300"]).unwrap();
+
+        assert!(actual.stats);
+        assert_eq!(actual.word_limit, Some(300));
+    }
+
+    #[test]
+    fn reads_a_grid_size_and_word_list() {
+        let actual = parse(&["--size", "4", "--words", "four.txt", "--require", "grim"]).unwrap();
+
+        assert_eq!(actual.size(), 4);
+        assert_eq!(actual.words, Some("four.txt".into()));
+        assert_eq!(parse(&[]).unwrap().size(), 5);
+        assert!(parse(&["--size", "2"]).is_err());
+        assert!(parse(&["--size", "8"]).is_err());
+        assert!(parse(&["--size", "4", "--require", "grime"]).is_err());
+        assert!(parse(&["--size", "6", "--template", "g????/?????/?????/?????/????t"]).is_err());
+    }
+
+    #[test]
+    fn reads_a_template() {
+        let actual = parse(&["--template", "g????/?????/?????/?????/????t"]).unwrap();
+
+        assert_eq!(actual.template.to_string(), "g????/?????/?????/?????/????t");
+    }
+
+    #[test]
+    fn a_malformed_template_is_an_error() {
+        assert!(parse(&["--template", "g????"]).is_err());
+    }
+
+    #[test]
+    fn reads_a_duplicate_policy() {
+        let actual = parse(&["--duplicates", "symmetric"]).unwrap();
+
+        assert_eq!(actual.duplicates, DuplicatePolicy::Symmetric);
+        assert!(parse(&["--duplicates", "some"]).is_err());
+    }
+
+    #[test]
+    fn counting_can_not_resume() {
+        assert!(parse(&["--count"]).unwrap().count);
+        assert!(parse(&["--count", "--resume", "state.txt"]).is_err());
+    }
+
+    #[test]
+    fn reads_a_finder() {
+        let actual = parse(&["--finder", "new-double-sided"]).unwrap();
+
+        assert_eq!(actual.finder, FinderKind::NewDoubleSided);
+        assert_eq!(parse(&[]).unwrap().finder, FinderKind::DoubleSidedBin);
+        assert!(parse(&["--finder", "fastest"]).is_err());
+    }
+
+    #[test]
+    fn reads_the_canonical_flag() {
+        assert!(parse(&["--canonical"]).unwrap().canonical);
+        assert!(!parse(&[]).unwrap().canonical);
+    }
+
+    #[test]
+    fn reads_a_shard() {
+        let actual = parse(&["--shard", "2/4"]).unwrap();
+
+        assert_eq!(
+            actual.shard,
+            Some(Shard {
+                number: 2,
+                count: 4
+            })
+        );
+        assert!(parse(&["--shard", "5/4"]).is_err());
+        assert!(parse(&["--shard"]).is_err());
+    }
+
+    #[test]
+    fn shards_of_one_search_share_a_fingerprint() {
+        let words = ["grime", "honor", "outdo"];
+        let scores = Commonness::from_ranked(&words);
+        let fingerprint = |args: &[&str]| parse(args).unwrap().fingerprint(&words, &scores);
+
+        assert_eq!(
+            fingerprint(&["--shard", "1/2"]),
+            fingerprint(&["--shard", "2/2"])
+        );
+        assert_ne!(
+            fingerprint(&["--shard", "1/2"]),
+            fingerprint(&["--shard", "2/2", "--canonical"])
+        );
+        assert_ne!(
+            fingerprint(&["--forbid", "grime"]),
+            fingerprint(&["--forbid", "honor"])
+        );
+        assert_ne!(
+            fingerprint(&[]),
+            parse(&[]).unwrap().fingerprint(&words[..2], &scores)
+        );
+    }
+
+    #[test]
+    fn checkpoints_only_resume_the_same_shard_of_the_same_search() {
+        let words = ["grime", "honor", "outdo"];
+        let scores = Commonness::from_ranked(&words);
+        let fingerprint =
+            |args: &[&str]| parse(args).unwrap().checkpoint_fingerprint(&words, &scores);
+
+        assert_eq!(
+            fingerprint(&["--resume", "a.txt"]),
+            fingerprint(&["--resume", "b.txt"])
+        );
+        assert_ne!(
+            fingerprint(&["--shard", "1/2"]),
+            fingerprint(&["--shard", "2/2"])
+        );
+        assert_ne!(
+            fingerprint(&["--finder", "double-sided-bin"]),
+            fingerprint(&["--finder", "double-sided-hash"])
+        );
+        assert_ne!(fingerprint(&[]), fingerprint(&["--canonical"]));
+    }
+
+    #[test]
+    fn fingerprints_the_scores_rather_than_where_they_came_from() {
+        let words = ["grime", "honor", "outdo"];
+        let scores = Commonness::from_ranked(&words);
+        let reversed = Commonness::from_ranked(&["outdo", "honor", "grime"]);
+        let fingerprint = |args: &[&str], scores| parse(args).unwrap().fingerprint(&words, scores);
+        let filtered = ["--min-score", "0.5", "--scores", "a.txt"];
+
+        assert_eq!(
+            fingerprint(&["--scores", "a.txt"], &scores),
+            fingerprint(&["--scores", "b.txt"], &scores)
+        );
+        assert_ne!(
+            fingerprint(&filtered, &scores),
+            fingerprint(&filtered, &reversed)
+        );
+        assert_eq!(
+            fingerprint(&[], &scores),
+            fingerprint(&[], &reversed),
+            "scores only change which solutions are printed with a --min-score"
+        );
+    }
+
+    #[test]
+    fn reads_required_and_forbidden_words() {
+        let actual = parse(&[
+            "--require",
+            "grime@row1",
+            "--forbid",
+            "level",
+            "--require",
+            "outdo",
+        ]);
+
+        let mut rules = WordRules::default();
+        rules.require("grime@row1".parse().unwrap());
+        rules.require("outdo".parse().unwrap());
+        rules.forbid("level");
+        assert_eq!(actual.unwrap().rules, rules);
+        assert!(parse(&["--require", "grime@row9x"]).is_err());
+        assert!(parse(&["--require", "grime@row9"]).is_err());
+        assert!(parse(&["--require", "grim"]).is_err());
+        assert!(parse(&["--forbid"]).is_err());
+    }
+
+    #[test]
+    fn reads_letter_rules() {
+        let actual = parse(&[
+            "--max-repeats",
+            "3",
+            "--ban-letters",
+            "qxz",
+            "--require-letters",
+            "e",
+        ]);
+
+        let mut letters = LetterRules::default();
+        letters.limit_repeats(3);
+        for letter in [AsciiChar::q, AsciiChar::x, AsciiChar::z] {
+            letters.ban(letter);
+        }
+        letters.require(AsciiChar::e);
+        assert_eq!(actual.unwrap().letters, letters);
+        assert!(parse(&["--ban-letters", "Q"]).is_err());
+        assert!(parse(&["--max-repeats", "many"]).is_err());
+    }
+
+    #[test]
+    fn reads_a_score_threshold() {
+        let actual = parse(&[
+            "--scores",
+            "scores.txt",
+            "--score-by",
+            "mean",
+            "--min-score",
+            "0.5",
+        ]);
+
+        let actual = actual.unwrap();
+        assert_eq!(actual.scores, Some("scores.txt".into()));
+        assert_eq!(actual.score_by, ScoreBy::Mean);
+        assert_eq!(actual.min_score, Some(0.5));
+        assert!(parse(&["--min-score", "high"]).is_err());
+        assert!(parse(&["--score-by", "max"]).is_err());
+        assert!(parse(&["--count", "--min-score", "0.5"]).is_err());
+    }
+
+    #[test]
+    fn reads_rank_options() {
+        let args = |args: &[&str]| RankArgs::parse(args.iter().map(|s| s.to_string()));
+
+        assert_eq!(args(&[]), Ok(RankArgs::default()));
+        assert_eq!(
+            args(&[
+                "mine.txt",
+                "--top",
+                "10",
+                "--show-scores",
+                "--score-by",
+                "mean"
+            ]),
+            Ok(RankArgs {
+                solutions: "mine.txt".into(),
+                top: Some(10),
+                show_scores: true,
+                score_by: ScoreBy::Mean,
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--top", "some"]).is_err());
+    }
+
+    #[test]
+    fn reads_estimate_options() {
+        let args = |args: &[&str]| EstimateArgs::parse(args.iter().map(|s| s.to_string()));
+
+        assert_eq!(args(&[]), Ok(EstimateArgs::default()));
+        assert_eq!(
+            args(&["2000", "--samples", "500", "--seed", "7"]),
+            Ok(EstimateArgs {
+                word_limit: Some(2000),
+                samples: 500,
+                seed: Some(7),
+            })
+        );
+        assert!(args(&["--samples", "0"]).is_err());
+        assert!(args(&["--seed"]).is_err());
+    }
+
+    #[test]
+    fn reads_merge_options() {
+        let args = |args: &[&str]| MergeArgs::parse(args.iter().map(|s| s.to_string()));
+
+        assert_eq!(
+            args(&["a.txt", "--output", "all.txt", "b.txt"]),
+            Ok(MergeArgs {
+                output: Some("all.txt".into()),
+                shard_files: vec!["a.txt".into(), "b.txt".into()],
+            })
+        );
+        assert!(args(&["--output", "all.txt"]).is_err());
+    }
+
+    #[test]
+    fn reads_selfplay_options() {
+        let args = |args: &[&str]| SelfplayArgs::parse(args.iter().map(|s| s.to_string()));
+
+        assert_eq!(args(&[]), Ok(SelfplayArgs::default()));
+        assert_eq!(
+            args(&[
+                "some.txt",
+                "--strategy",
+                "first-candidate",
+                "--sample",
+                "100",
+                "--seed",
+                "7"
+            ]),
+            Ok(SelfplayArgs {
+                solutions: "some.txt".into(),
+                strategy: StrategyKind::FirstCandidate,
+                sample: Some(100),
+                seed: Some(7),
+                ..Default::default()
+            })
+        );
+        assert!(args(&["--strategy", "random"]).is_err());
+        assert!(args(&["--max-guesses", "0"]).is_err());
+        assert!(args(&["--sample"]).is_err());
+    }
+
+    #[test]
+    fn resume_without_a_file_is_an_error() {
+        assert!(parse(&["--resume"]).is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use std::{collections::HashMap, fmt::Display, fs, path::Path, str::FromStr};
+
+/// How common each word is, with higher scores for more common words
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Commonness {
+    scores: HashMap<String, f64>,
+}
+
+impl Commonness {
+    /// Scores words by their place in a list sorted from most to least common, as `words.txt`
+    /// is, going from 1 for the first word down towards 0 for the last
+    pub fn from_ranked(words: &[impl AsRef<str>]) -> Self {
+        let len = words.len() as f64;
+        let scores = words
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(rank, word)| (word.as_ref().to_string(), 1.0 - rank as f64 / len))
+            .collect();
+        Self { scores }
+    }
+
+    /// Reads a scores file, or ranks `words` by their order if there isn't one
+    pub fn load(path: Option<&Path>, words: &[impl AsRef<str>]) -> Result<Self, String> {
+        match path {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|e| format!("Couldn't read {}: {e}", path.display()))?
+                .parse(),
+            None => Ok(Self::from_ranked(words)),
+        }
+    }
+
+    /// The word's score, or 0 for words it doesn't know
+    pub fn score(&self, word: &str) -> f64 {
+        self.scores.get(word).copied().unwrap_or(0.0)
+    }
+}
+
+/// Writes one `word score` pair per line in alphabetical order, as a scores file
+impl Display for Commonness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut words = self.scores.keys().collect::<Vec<_>>();
+        words.sort();
+        for word in words {
+            writeln!(f, "{word} {}", self.scores[word])?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads one `word score` pair per line, skipping blank lines and `#` comments
+impl FromStr for Commonness {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut scores = HashMap::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                format!(
+                    "line {}: expected a word and a score, got {line:?}",
+                    number + 1
+                )
+            };
+            let (word, score) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            let score = score.trim().parse::<f64>().map_err(|_| invalid())?;
+            scores.insert(word.to_string(), score);
+        }
+        Ok(Self { scores })
+    }
+}
+
+/// How common a solution's words are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    /// The score of its rarest word
+    pub min: f64,
+    /// The average score of its rows and columns
+    pub mean: f64,
+}
+
+impl Score {
+    pub fn by(&self, by: ScoreBy) -> f64 {
+        match by {
+            ScoreBy::Min => self.min,
+            ScoreBy::Mean => self.mean,
+        }
+    }
+}
+
+impl Display for Score {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "min {:.3} mean {:.3}", self.min, self.mean)
+    }
+}
+
+/// Which part of a [`Score`] to sort and filter solutions by
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScoreBy {
+    /// Puts solutions with no rare words first, however common the rest are
+    #[default]
+    Min,
+    Mean,
+}
+
+impl Display for ScoreBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Min => "min",
+            Self::Mean => "mean",
+        })
+    }
+}
+
+impl FromStr for ScoreBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "min" => Ok(Self::Min),
+            "mean" => Ok(Self::Mean),
+            _ => Err(format!("unknown score {s:?}, expected min or mean")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Solution;
+
+    #[test]
+    fn ranks_score_from_one_down() {
+        let commonness = Commonness::from_ranked(&["which", "there", "their", "about"]);
+
+        assert_eq!(commonness.score("which"), 1.0);
+        assert_eq!(commonness.score("their"), 0.5);
+        assert_eq!(commonness.score("about"), 0.25);
+        assert_eq!(commonness.score("yobbo"), 0.0);
+    }
+
+    #[test]
+    fn reads_a_scores_file() {
+        let commonness: Commonness = "# word score\ngrime 0.5\n\nhonor\t2\n".parse().unwrap();
+
+        assert_eq!(commonness.score("grime"), 0.5);
+        assert_eq!(commonness.score("honor"), 2.0);
+        assert!("grime".parse::<Commonness>().is_err());
+        assert!("grime lots".parse::<Commonness>().is_err());
+    }
+
+    #[test]
+    fn writes_a_scores_file_it_can_read_back() {
+        let commonness = Commonness::from_ranked(&["which", "there", "their"]);
+
+        let text = commonness.to_string();
+
+        assert_eq!(text.lines().next(), Some("their 0.33333333333333337"));
+        assert_eq!(text.parse(), Ok(commonness));
+    }
+
+    #[test]
+    fn scores_solutions_by_their_rows_and_columns() {
+        let sol = Solution::new(["grime", "honor", "outdo", "steed", "terse"]);
+        let commonness: Commonness = "grime 1\nhonor 1\noutdo 1\nsteed 1\nterse 1\n\
+            ghost 1\nroute 1\ninter 1\nmodes 1\nerode 0"
+            .parse()
+            .unwrap();
+
+        let score = sol.score(&commonness);
+
+        assert_eq!(score.min, 0.0);
+        assert_eq!(score.mean, 0.9);
+        assert_eq!(score.by(ScoreBy::Mean), 0.9);
+        assert_eq!(sol.transpose().score(&commonness), score);
+    }
+}
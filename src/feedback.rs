@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    Solution, Word,
+    finder::{LetterPlayed, PuzzleViewModel, RowHint},
+};
+
+/// Everything one guess shows about a solution, packed into a single integer so it can be
+/// worked out and compared without allocating.
+///
+/// The lowest `N * N` bits are the squares the guess revealed, row by row. The next `N * N`
+/// say which of the guess's letters each row hints at, and the last `N` which of them are in
+/// the solution at all. Letters are given by where they first appear in the guess, so two
+/// solutions showing a guess the same way always get the same `Feedback`. That fits grids up to
+/// seven squares across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Feedback<const N: usize = 5>(u128);
+
+impl<const N: usize> Feedback<N> {
+    const HINTS: usize = N * N;
+    const IN_SOLUTION: usize = 2 * N * N;
+
+    pub fn new(solution: &Solution<N>, guess: &Word<N>) -> Self {
+        const { assert!(2 * N * N + N <= 128, "the feedback doesn't fit in a u128") };
+        let guess = &guess.0;
+        let mut bits = 0;
+        for (y, row) in solution.rows.iter().enumerate() {
+            for (x, (letter, guessed)) in row.0.iter().zip(guess).enumerate() {
+                if letter == guessed {
+                    bits |= 1 << (y * N + x);
+                }
+            }
+        }
+
+        for i in (0..N).filter(|&i| !guess[..i].contains(&guess[i])) {
+            let letter = guess[i];
+            for (y, row) in solution.rows.iter().enumerate() {
+                let hinted = (0..N).any(|x| bits & (1 << (y * N + x)) == 0 && row.0[x] == letter);
+                if hinted {
+                    bits |= 1 << (Self::HINTS + y * N + i);
+                }
+            }
+            if solution.rows.iter().any(|row| row.0.contains(&letter)) {
+                bits |= 1 << (Self::IN_SOLUTION + i);
+            }
+        }
+        Self(bits)
+    }
+
+    pub fn is_revealed(&self, y: usize, x: usize) -> bool {
+        self.0 & (1 << (y * N + x)) != 0
+    }
+
+    /// Whether every square was revealed, which only happens if the guess filled the grid
+    pub fn is_finished(&self) -> bool {
+        (0..N).all(|y| (0..N).all(|x| self.is_revealed(y, x)))
+    }
+
+    /// The view a player would see after only making `guess`, which has to be the guess this
+    /// feedback came from
+    pub fn to_view(&self, guess: &Word<N>) -> PuzzleViewModel<N> {
+        let letters = guess.0;
+        let firsts = (0..N).filter(|&i| !letters[..i].contains(&letters[i]));
+        let hinted = |y: usize, i: usize| self.0 & (1 << (Self::HINTS + y * N + i)) != 0;
+
+        let grid = std::array::from_fn(|y| {
+            std::array::from_fn(|x| self.is_revealed(y, x).then_some(letters[x]))
+        });
+        let hints: [RowHint; N] = std::array::from_fn(|y| {
+            firsts
+                .clone()
+                .filter(|&i| hinted(y, i))
+                .map(|i| letters[i])
+                .collect()
+        });
+        let alphabet = firsts
+            .map(|i| {
+                let played = if self.0 & (1 << (Self::IN_SOLUTION + i)) == 0 {
+                    LetterPlayed::NotInSolution
+                } else if (0..N).any(|y| hinted(y, i)) {
+                    LetterPlayed::PartiallyUsed
+                } else {
+                    LetterPlayed::AllUsed
+                };
+                (letters[i], played)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        PuzzleViewModel {
+            guesses: vec![guess.clone()],
+            is_finished: self.is_finished(),
+            grid,
+            hints,
+            alphabet,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::finder::Puzzle;
+
+    #[test]
+    fn shows_the_same_as_the_puzzle_view() {
+        let solutions: [Solution; 3] = [
+            "grime,honor,outdo,steed,terse".parse().unwrap(),
+            "aback,algae,rally,grove,hewed".parse().unwrap(),
+            "small,movie,alive,stark,hones".parse().unwrap(),
+        ];
+
+        for solution in &solutions {
+            for guess in [
+                "arose", "steed", "aback", "ricky", "eerie", "hewed", "grime",
+            ] {
+                let guess: Word = guess.into();
+                let mut puzzle = Puzzle::new(solution.clone());
+                puzzle.guess(guess.clone());
+
+                let feedback = Feedback::new(solution, &guess);
+
+                assert_eq!(
+                    feedback.to_view(&guess),
+                    puzzle.view(),
+                    "{solution} {guess:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fits_six_by_six_grids() {
+        let solution =
+            Solution::<6>::new(["abacus", "bonito", "aneled", "citron", "utopia", "sodden"]);
+
+        for guess in ["abacus", "banana", "dodder", "zzzzzz"] {
+            let guess = Word::<6>::from(guess);
+            let mut puzzle = Puzzle::new(solution.clone());
+            puzzle.guess(guess.clone());
+
+            assert_eq!(
+                Feedback::new(&solution, &guess).to_view(&guess),
+                puzzle.view(),
+                "{guess:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn is_the_same_for_solutions_giving_the_same_view() {
+        let hewed: Solution = "aback,algae,rally,grove,hewed".parse().unwrap();
+        let hewer: Solution = "aback,algae,rally,grove,hewer".parse().unwrap();
+
+        assert_eq!(
+            Feedback::new(&hewed, &"algae".into()),
+            Feedback::new(&hewer, &"algae".into())
+        );
+        assert_ne!(
+            Feedback::new(&hewed, &"hewed".into()),
+            Feedback::new(&hewer, &"hewed".into())
+        );
+    }
+
+    #[test]
+    fn finishes_when_the_guess_fills_the_grid() {
+        let solution = Solution::<3>::new(["aaa", "aaa", "aaa"]);
+
+        assert!(Feedback::new(&solution, &"aaa".into()).is_finished());
+        assert!(!Feedback::new(&solution, &"aab".into()).is_finished());
+    }
+}
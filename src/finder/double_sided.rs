@@ -6,39 +6,198 @@ use itertools::Itertools;
 #[cfg(feature = "multi-thread")]
 use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
-use crate::{RangeFinder, Solution, SolutionFinder, Word};
-
+use crate::{
+    FirstRowFinder, RangeFinder, Solution, SolutionCounts, SolutionFinder, Word,
+    cancel::{CancelToken, NodeMeter},
+    finder::SearchStats,
+    progress::{Progress, ProgressTracker},
+    rules::WordRules,
+    shard::Shard,
+};
+
+/// Finds every 5×5 solution by placing a row, then a column, then a row and so on from the top
+/// left corner.
+///
+/// The order it fills them in is written out for five letters, so unlike the other finders it
+/// only handles 5×5 grids, and `solve` turns down any other `--size` for it.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DoubleSidedFinder<R: for<'a> RangeFinder<'a> + Send + Sync> {
     words: Vec<Word>,
     range_finder: R,
+    shard: Shard,
+    canonical: bool,
+    rules: WordRules,
 }
 
 impl<R: for<'b> RangeFinder<'b> + Send + Sync> DoubleSidedFinder<R> {
+    /// Only searches the starting rows in `shard`, counted in sorted order
+    pub fn with_shard(self, shard: Shard) -> Self {
+        Self { shard, ..self }
+    }
+
+    /// Only finds the canonical form of each solution rather than it and its transpose
+    pub fn with_canonical_only(self) -> Self {
+        Self {
+            canonical: true,
+            ..self
+        }
+    }
+
+    /// Only finds solutions meeting `rules`, leaving out the words they forbid and cutting off
+    /// branches with nowhere left for a required word
+    pub fn with_rules(self, rules: WordRules) -> Self {
+        let words = self
+            .words
+            .into_iter()
+            .filter(|word| rules.allows(&String::from(word.clone())))
+            .collect_vec();
+        Self {
+            range_finder: R::init(&words),
+            words,
+            rules,
+            ..self
+        }
+    }
+
+    /// The indexes of the starting rows this finder searches
+    fn starting_indexes(&self) -> Vec<usize> {
+        (0..self.words.len())
+            .filter(|&i| self.shard.contains(i))
+            .collect_vec()
+    }
+
     fn find_solutions(&self) -> Vec<Solution> {
-        let words = self.words.iter().enumerate();
+        let starting_indexes = self.starting_indexes();
         #[cfg(feature = "multi-thread")]
         {
-            words
-                .collect::<Vec<_>>()
+            starting_indexes
                 .par_iter()
-                .flat_map(|(i, _)| {
-                    let mut inner = Inner::new(*i, &self.words, &self.range_finder);
-                    inner.fill_first_column().into_par_iter()
-                })
+                .flat_map(|&i| self.find_from(i).into_par_iter())
                 .collect()
         }
 
         #[cfg(not(feature = "multi-thread"))]
         {
-            words
-                .flat_map(|(i, _)| {
-                    let mut inner = Inner::new(i, &self.words, &self.range_finder);
-                    inner.fill_first_column().into_iter()
-                })
+            starting_indexes
+                .into_iter()
+                .flat_map(|i| self.find_from(i).into_iter())
                 .collect::<Vec<_>>()
         }
     }
+
+    fn find_from(&self, starting_index: usize) -> Vec<Solution> {
+        let mut solutions = Vec::new();
+        let mut collect = |sol| solutions.push(sol);
+        self.search_from(starting_index, Some(&mut collect), None);
+        solutions
+    }
+
+    /// Searches every solution from one starting row, counting them and the words it placed,
+    /// and giving up early if `token` stops the search
+    fn search_from<'s>(
+        &self,
+        starting_index: usize,
+        sink: Option<&'s mut dyn FnMut(Solution)>,
+        token: Option<&'s CancelToken>,
+    ) -> SearchStats {
+        let mut inner = Inner::new(starting_index, &self.words, &self.range_finder, sink)
+            .canonical_only(self.canonical)
+            .with_rules(&self.rules)
+            .until(token);
+        inner.fill_first_column();
+        SearchStats {
+            nodes: inner.nodes,
+            solutions: inner.count,
+            stopped: inner.stopped(),
+            ..Default::default()
+        }
+    }
+}
+
+impl<R: for<'b> RangeFinder<'b> + Send + Sync> FirstRowFinder for DoubleSidedFinder<R> {
+    fn find_each_observed(
+        &self,
+        sink: &(dyn Fn(Solution) + Sync),
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> SearchStats {
+        let starting_indexes = self.starting_indexes();
+        let tracker = ProgressTracker::new(starting_indexes.len());
+        let search = |&i: &usize| {
+            let mut emit = |sol| sink(sol);
+            let stats = self.search_from(i, Some(&mut emit), None);
+            observer(tracker.finish_branch(stats.solutions, stats.nodes));
+            stats
+        };
+
+        #[cfg(feature = "multi-thread")]
+        {
+            starting_indexes.par_iter().map(search).sum()
+        }
+
+        #[cfg(not(feature = "multi-thread"))]
+        {
+            starting_indexes.iter().map(search).sum()
+        }
+    }
+
+    fn count_by_first_row(
+        &self,
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> (SolutionCounts, SearchStats) {
+        let starting_indexes = self.starting_indexes();
+        let tracker = ProgressTracker::new(starting_indexes.len());
+        let count = |&i: &usize| {
+            let stats = self.search_from(i, None, None);
+            observer(tracker.finish_branch(stats.solutions, stats.nodes));
+            (String::from(self.words[i].clone()), stats)
+        };
+
+        #[cfg(feature = "multi-thread")]
+        let branches = starting_indexes.par_iter().map(count).collect::<Vec<_>>();
+
+        #[cfg(not(feature = "multi-thread"))]
+        let branches = starting_indexes.iter().map(count).collect_vec();
+
+        let stats = branches.iter().map(|(_, stats)| *stats).sum();
+        let counts = branches
+            .into_iter()
+            .map(|(word, stats)| (word, stats.solutions))
+            .collect();
+        (counts, stats)
+    }
+
+    fn find_by_first_row(
+        &self,
+        is_finished: &(dyn Fn(&str) -> bool + Sync),
+        on_finished: &(dyn Fn(&str, Vec<Solution>) + Sync),
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> SearchStats {
+        let starting_words = self
+            .starting_indexes()
+            .into_iter()
+            .map(|i| (i, String::from(self.words[i].clone())))
+            .filter(|(_, word)| !is_finished(word))
+            .collect_vec();
+        let tracker = ProgressTracker::new(starting_words.len());
+        let search = |(i, starting_word): &(usize, String)| {
+            let mut solutions = Vec::new();
+            let mut collect = |sol| solutions.push(sol);
+            let stats = self.search_from(*i, Some(&mut collect), None);
+            on_finished(starting_word, solutions);
+            observer(tracker.finish_branch(stats.solutions, stats.nodes));
+            stats
+        };
+
+        #[cfg(feature = "multi-thread")]
+        {
+            starting_words.par_iter().map(search).sum()
+        }
+
+        #[cfg(not(feature = "multi-thread"))]
+        {
+            starting_words.iter().map(search).sum()
+        }
+    }
 }
 
 impl<'a, R: for<'b> RangeFinder<'b> + Send + Sync> SolutionFinder<'a> for DoubleSidedFinder<R> {
@@ -52,23 +211,63 @@ impl<'a, R: for<'b> RangeFinder<'b> + Send + Sync> SolutionFinder<'a> for Double
         Self {
             range_finder: R::init(&words),
             words,
+            shard: Shard::default(),
+            canonical: false,
+            rules: WordRules::default(),
         }
     }
 
     fn find(&self) -> Vec<Solution> {
         self.find_solutions()
     }
+
+    fn find_each_until(&self, sink: &(dyn Fn(Solution) + Sync), token: &CancelToken) -> bool {
+        let starting_indexes = self.starting_indexes();
+        let search = |&i: &usize| {
+            let mut emit = |sol| sink(sol);
+            !self.search_from(i, Some(&mut emit), Some(token)).stopped
+        };
+
+        #[cfg(feature = "multi-thread")]
+        {
+            starting_indexes.par_iter().all(search)
+        }
+
+        #[cfg(not(feature = "multi-thread"))]
+        {
+            starting_indexes.iter().all(search)
+        }
+    }
 }
 
-struct Inner<'a, R: RangeFinder<'a>> {
+/// What a search without any word rules checks against
+static NO_RULES: WordRules = WordRules::NONE;
+
+struct Inner<'a, 's, R: RangeFinder<'a>> {
     row_indexes: Vec<usize>,
     column_indexes: Vec<usize>,
     words: &'a [Word],
     range_finder: &'a R,
+    /// Where finished solutions go, or `None` to only count them
+    sink: Option<&'s mut dyn FnMut(Solution)>,
+    /// Whether to only hand on the canonical one of each solution and its transpose
+    canonical: bool,
+    /// Words the grid, or its transpose, has to have
+    rules: &'a WordRules,
+    count: u64,
+    /// Words placed so far
+    nodes: u64,
+    /// Checks in with the search's [`CancelToken`], if it has one
+    meter: Option<NodeMeter<'s>>,
 }
 
-impl<'a, R: RangeFinder<'a>> Inner<'a, R> {
-    fn new(starting_index: usize, words: &'a [Word], range_finder: &'a R) -> Self {
+impl<'a, 's, R: RangeFinder<'a>> Inner<'a, 's, R> {
+    fn new(
+        starting_index: usize,
+        words: &'a [Word],
+        range_finder: &'a R,
+        sink: Option<&'s mut dyn FnMut(Solution)>,
+    ) -> Self {
         let mut rows = Vec::with_capacity(5);
         rows.push(starting_index);
         Self {
@@ -76,32 +275,84 @@ impl<'a, R: RangeFinder<'a>> Inner<'a, R> {
             column_indexes: Vec::with_capacity(5),
             words,
             range_finder,
+            sink,
+            canonical: false,
+            rules: &NO_RULES,
+            count: 0,
+            nodes: 0,
+            meter: None,
+        }
+    }
+
+    fn canonical_only(self, canonical: bool) -> Self {
+        Self { canonical, ..self }
+    }
+
+    fn with_rules(self, rules: &'a WordRules) -> Self {
+        Self { rules, ..self }
+    }
+
+    fn until(self, token: Option<&'s CancelToken>) -> Self {
+        Self {
+            meter: token.map(NodeMeter::new),
+            ..self
+        }
+    }
+
+    /// Counts one more word placed
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        if let Some(meter) = self.meter.as_mut() {
+            meter.tick();
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        self.meter.as_ref().is_some_and(NodeMeter::stopped)
+    }
+
+    /// The letter filled in at row `y` and column `x`, if any word crossing it has been placed
+    fn letter(&self, y: usize, x: usize) -> Option<u8> {
+        if let Some(&row) = self.row_indexes.get(y) {
+            Some(self.words[row].0[x].as_byte())
+        } else {
+            let column = self.column_indexes.get(x)?;
+            Some(self.words[*column].0[y].as_byte())
         }
     }
 
-    fn fill_first_column(&mut self) -> Vec<Solution> {
+    /// Whether the grid, or its transpose, could still have every required word
+    fn fits_rules(&self) -> bool {
+        self.rules.could_hold::<5>(|y, x| self.letter(y, x))
+            || self.rules.could_hold::<5>(|y, x| self.letter(x, y))
+    }
+
+    fn fill_first_column(&mut self) {
         let starting_index = self.row_indexes[0];
-        R::range(
+        let range = R::range(
             self.range_finder,
             self.words[self.row_indexes[0]].0[0..1].try_into().unwrap(),
-        )
-        .filter(|&i| i > starting_index)
-        .flat_map(|i| {
+        );
+        for i in range.filter(|&i| i > starting_index) {
+            if self.stopped() {
+                break;
+            }
             self.column_indexes.push(i);
-            let iter = self.fill_row_1().into_iter();
+            self.count_node();
+            if self.fits_rules() {
+                self.fill_row_1();
+            }
             self.column_indexes.pop();
-            iter
-        })
-        .collect()
+        }
     }
 
-    fn fill_row_1(&mut self) -> Vec<Solution> {
+    fn fill_row_1(&mut self) {
         let start = [0].map(|col| self.words[self.column_indexes[col]].0[1]);
         let placed_words = [self.row_indexes[0], self.column_indexes[0]];
         self.fill_middle_row_inner(&Self::fill_column_1, &start, placed_words)
     }
 
-    fn fill_column_1(&mut self) -> Vec<Solution> {
+    fn fill_column_1(&mut self) {
         let start = [0, 1].map(|i| self.words[self.row_indexes[i]].0[1]);
         let placed_words = [
             self.row_indexes[0],
@@ -111,13 +362,13 @@ impl<'a, R: RangeFinder<'a>> Inner<'a, R> {
         self.fill_middle_column_inner(&Self::fill_row_2, &start, placed_words)
     }
 
-    fn fill_row_2(&mut self) -> Vec<Solution> {
+    fn fill_row_2(&mut self) {
         let unfinished_columns =
             [2, 3, 4].map(|col| [0, 1].map(|row| self.words[self.row_indexes[row]].0[col]));
         for column in unfinished_columns {
             let range = R::range(self.range_finder, &column);
             if range.is_empty() {
-                return Vec::new();
+                return;
             }
         }
         let placed_words = [
@@ -130,13 +381,13 @@ impl<'a, R: RangeFinder<'a>> Inner<'a, R> {
         self.fill_middle_row_inner(&Self::fill_column_2, &start, placed_words)
     }
 
-    fn fill_column_2(&mut self) -> Vec<Solution> {
+    fn fill_column_2(&mut self) {
         let unfinished_rows =
             [3, 4].map(|row| [0, 1].map(|col| self.words[self.column_indexes[col]].0[row]));
         for row in unfinished_rows {
             let range = R::range(self.range_finder, &row);
             if range.is_empty() {
-                return Vec::new();
+                return;
             }
         }
         let placed_words = [
@@ -150,7 +401,7 @@ impl<'a, R: RangeFinder<'a>> Inner<'a, R> {
         self.fill_middle_column_inner(&Self::fill_row_3, &start, placed_words)
     }
 
-    fn fill_row_3(&mut self) -> Vec<Solution> {
+    fn fill_row_3(&mut self) {
         let start = [0, 1, 2].map(|col| self.words[self.column_indexes[col]].0[3]);
         let placed_words = [
             self.row_indexes[0],
@@ -163,7 +414,7 @@ impl<'a, R: RangeFinder<'a>> Inner<'a, R> {
         self.fill_middle_row_inner(&Self::fill_column_3, &start, placed_words)
     }
 
-    fn fill_column_3(&mut self) -> Vec<Solution> {
+    fn fill_column_3(&mut self) {
         let start = [0, 1, 2, 3].map(|i| self.words[self.row_indexes[i]].0[3]);
         let placed_words = [
             self.row_indexes[0],
@@ -179,79 +430,94 @@ impl<'a, R: RangeFinder<'a>> Inner<'a, R> {
 
     fn fill_middle_row_inner<const N: usize>(
         &mut self,
-        func: &dyn Fn(&mut Self) -> Vec<Solution>,
+        func: &dyn Fn(&mut Self),
         start: &[AsciiChar],
         placed_words: [usize; N],
-    ) -> Vec<Solution> {
-        R::range(self.range_finder, start)
-            .except_for(placed_words)
-            .flat_map(|i| {
-                self.row_indexes.push(i);
-                let iter = func(self).into_iter();
-                self.row_indexes.pop();
-                iter
-            })
-            .collect()
+    ) {
+        for i in R::range(self.range_finder, start).except_for(placed_words) {
+            if self.stopped() {
+                break;
+            }
+            self.row_indexes.push(i);
+            self.count_node();
+            if self.fits_rules() {
+                func(self);
+            }
+            self.row_indexes.pop();
+        }
     }
 
     fn fill_middle_column_inner<const N: usize>(
         &mut self,
-        func: &dyn Fn(&mut Self) -> Vec<Solution>,
+        func: &dyn Fn(&mut Self),
         start: &[AsciiChar],
         placed_words: [usize; N],
-    ) -> Vec<Solution> {
-        R::range(self.range_finder, start)
-            .except_for(placed_words)
-            .flat_map(|i| {
-                self.column_indexes.push(i);
-                let iter = func(self).into_iter();
-                self.column_indexes.pop();
-                iter
-            })
-            .collect()
-    }
-
-    fn fill_last_slot(&mut self) -> Vec<Solution> {
+    ) {
+        for i in R::range(self.range_finder, start).except_for(placed_words) {
+            if self.stopped() {
+                break;
+            }
+            self.column_indexes.push(i);
+            self.count_node();
+            if self.fits_rules() {
+                func(self);
+            }
+            self.column_indexes.pop();
+        }
+    }
+
+    fn fill_last_slot(&mut self) {
         let start = [0, 1, 2, 3].map(|i| self.words[self.column_indexes[i]].0[4]);
 
-        R::range(self.range_finder, &start)
-            .flat_map(|i| {
-                self.row_indexes.push(i);
-                let k = if self.is_valid() {
-                    let sols = match self.last_column() {
-                        Some(last_column) => {
-                            let mut columns = self.column_indexes.clone();
-                            columns.push(last_column);
-                            vec![
-                                Solution::new(
-                                    columns
-                                        .iter()
-                                        .map(|&i| self.words[i].0.as_ascii_str().unwrap())
-                                        .collect::<Vec<_>>()
-                                        .try_into()
-                                        .unwrap(),
-                                ),
-                                Solution::new(
-                                    self.row_indexes
-                                        .iter()
-                                        .map(|&i| self.words[i].0.as_ascii_str().unwrap())
-                                        .collect::<Vec<_>>()
-                                        .try_into()
-                                        .unwrap(),
-                                ),
-                            ]
-                        }
-                        None => Vec::new(),
-                    };
-
-                    sols.into_iter()
-                } else {
-                    vec![].into_iter()
-                };
-                self.row_indexes.pop();
-                k
-            })
-            .collect()
+        for i in R::range(self.range_finder, &start) {
+            if self.stopped() {
+                break;
+            }
+            self.row_indexes.push(i);
+            self.count_node();
+            if self.is_valid()
+                && let Some(last_column) = self.last_column()
+            {
+                self.emit(last_column);
+            }
+            self.row_indexes.pop();
+        }
+    }
+
+    /// Counts the finished grid and its transpose, only building them if there's a sink or
+    /// rules to check them against
+    fn emit(&mut self, last_column: usize) {
+        if self.sink.is_none() && self.rules.required().is_empty() {
+            self.count += if self.canonical { 1 } else { 2 };
+            return;
+        }
+        let mut columns = self.column_indexes.clone();
+        columns.push(last_column);
+        let found = [
+            self.solution_from(&columns),
+            self.solution_from(&self.row_indexes),
+        ]
+        .into_iter()
+        .filter(|sol| self.rules.matches(sol));
+        let found = match self.canonical {
+            true => found.min().into_iter().collect_vec(),
+            false => found.collect_vec(),
+        };
+        self.count += found.len() as u64;
+        if let Some(sink) = self.sink.as_mut() {
+            found.into_iter().for_each(sink);
+        }
+    }
+
+    fn solution_from(&self, indexes: &[usize]) -> Solution {
+        Solution::new(
+            indexes
+                .iter()
+                .map(|&i| self.words[i].0.as_ascii_str().unwrap())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        )
     }
 
     fn is_valid(&self) -> bool {
@@ -342,7 +608,7 @@ impl<I: Iterator<Item = usize>, const N: usize> ExceptForExt<N> for I {}
 
 #[cfg(test)]
 mod test {
-    use crate::{BinSearchRange, SolutionFinder};
+    use crate::{BinSearchRange, FirstRowFinder, SolutionFinder, finder::TEST_WORDS};
 
     use super::*;
 
@@ -381,4 +647,78 @@ mod test {
         println!("{sols:?}");
         assert_eq!(sols.len(), 2);
     }
+
+    #[test]
+    fn counting_matches_the_solutions_from_each_starting_word() {
+        let words = TEST_WORDS;
+        let f = DoubleSidedFinder::<BinSearchRange>::new(&words);
+        let found = std::sync::Mutex::new(vec![]);
+        f.find_by_first_row(
+            &|_| false,
+            &|word, sols| {
+                found
+                    .lock()
+                    .unwrap()
+                    .push((word.to_string(), sols.len() as u64))
+            },
+            &|_| {},
+        );
+        let expected = found
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .collect::<SolutionCounts>();
+
+        let (counts, stats) = f.count_by_first_row(&|_| {});
+
+        assert_eq!(counts, expected);
+        assert_eq!(counts.total(), f.find().len() as u64);
+        assert_eq!(stats.solutions, counts.total());
+    }
+
+    #[test]
+    fn canonical_only_finds_one_of_each_transposed_pair() {
+        let words = TEST_WORDS;
+        let everything = DoubleSidedFinder::<BinSearchRange>::new(&words).find();
+        let f = DoubleSidedFinder::<BinSearchRange>::new(&words).with_canonical_only();
+
+        let canonical = f.find();
+
+        assert_eq!(canonical.len() * 2, everything.len());
+        assert!(canonical.iter().all(|sol| sol.is_canonical()));
+        assert!(
+            everything
+                .iter()
+                .all(|sol| canonical.contains(&sol.canonical()))
+        );
+        assert_eq!(
+            f.count_by_first_row(&|_| {}).0.total(),
+            canonical.len() as u64
+        );
+    }
+
+    #[test]
+    fn word_rules_keep_only_the_solutions_meeting_them() {
+        let words = TEST_WORDS;
+        let everything = DoubleSidedFinder::<BinSearchRange>::new(&words).find();
+        let mut rules = WordRules::default();
+        rules.require("apple@row2".parse().unwrap());
+        rules.forbid("ghost");
+        let mut expected = everything
+            .into_iter()
+            .filter(|sol| rules.matches(sol))
+            .collect_vec();
+        expected.sort();
+        let f = DoubleSidedFinder::<BinSearchRange>::new(&words).with_rules(rules);
+
+        let mut actual = f.find();
+        actual.sort();
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual, expected);
+        assert_eq!(
+            f.count_by_first_row(&|_| {}).0.total(),
+            expected.len() as u64
+        );
+    }
 }
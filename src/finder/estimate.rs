@@ -0,0 +1,184 @@
+use std::{fmt::Display, time::Duration};
+
+use rand::Rng;
+
+use crate::{finder::new_double_sided, progress::format_duration};
+
+/// What one random path down a search tree says about the size of the whole tree
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct TreeSample {
+    pub nodes: f64,
+    pub solutions: f64,
+}
+
+/// A guess made from random samples, with a 95% confidence interval around it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub estimate: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Interval {
+    /// Assumes the mean of the samples is roughly normal. Knuth's estimates have a long tail,
+    /// so with few samples the real interval is wider than this one.
+    fn from_samples(values: &[f64]) -> Self {
+        let n = values.len().max(1) as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+        let margin = 1.96 * (variance / n).sqrt();
+        Self {
+            estimate: mean,
+            low: (mean - margin).max(0.0),
+            high: mean + margin,
+        }
+    }
+
+    fn scale(self, factor: f64) -> Self {
+        Self {
+            estimate: self.estimate * factor,
+            low: self.low * factor,
+            high: self.high * factor,
+        }
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.3e} (95% between {:.3e} and {:.3e})",
+            self.estimate, self.low, self.high
+        )
+    }
+}
+
+/// How big a full search of a word list would be, and how long it would take
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub samples: usize,
+    pub nodes: Interval,
+    pub solutions: Interval,
+    /// How fast whole branches were searched on this machine, on one thread
+    pub nodes_per_second: f64,
+    /// Threads a full search would be split across
+    pub threads: usize,
+}
+
+impl Estimate {
+    /// Seconds of CPU time the whole search would take
+    pub fn cpu_seconds(&self) -> Interval {
+        self.nodes
+            .scale(1.0 / self.nodes_per_second.max(f64::EPSILON))
+    }
+
+    /// Seconds the whole search would take with every thread busy
+    pub fn wall_seconds(&self) -> Interval {
+        self.cpu_seconds().scale(1.0 / self.threads as f64)
+    }
+}
+
+impl Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let time = |seconds: Interval| {
+            let duration = |seconds| format_duration(Duration::from_secs_f64(seconds));
+            format!(
+                "{} (95% between {} and {})",
+                duration(seconds.estimate),
+                duration(seconds.low),
+                duration(seconds.high)
+            )
+        };
+        writeln!(f, "samples:   {}", self.samples)?;
+        writeln!(f, "nodes:     {}", self.nodes)?;
+        writeln!(f, "solutions: {}", self.solutions)?;
+        writeln!(f, "speed:     {:.0} nodes/s", self.nodes_per_second)?;
+        writeln!(f, "cpu time:  {}", time(self.cpu_seconds()))?;
+        writeln!(f, "threads:   {}", self.threads)?;
+        writeln!(f, "wall time: {}", time(self.wall_seconds()))
+    }
+}
+
+/// Guesses how big a full `new_double_sided` search of `words` is by following `samples` random
+/// paths down its tree, and how fast this machine searches it by timing whole branches for
+/// about `calibration`
+pub fn estimate_search<const N: usize>(
+    words: &[&str],
+    samples: usize,
+    calibration: Duration,
+    rng: &mut impl Rng,
+) -> Estimate {
+    let tree = new_double_sided::sample_tree::<N>(words, samples, rng);
+    let nodes = tree.iter().map(|sample| sample.nodes).collect::<Vec<_>>();
+    let solutions = tree
+        .iter()
+        .map(|sample| sample.solutions)
+        .collect::<Vec<_>>();
+
+    let (timed_nodes, took) = new_double_sided::time_branches::<N>(words, calibration, rng);
+
+    #[cfg(feature = "multi-thread")]
+    let threads = rayon::current_num_threads();
+
+    #[cfg(not(feature = "multi-thread"))]
+    let threads = 1;
+
+    Estimate {
+        samples,
+        nodes: Interval::from_samples(&nodes),
+        solutions: Interval::from_samples(&solutions),
+        nodes_per_second: timed_nodes as f64 / took.as_secs_f64().max(f64::EPSILON),
+        threads,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::finder::{
+        TEST_WORDS,
+        new_double_sided::{Constraints, count_by_first_row},
+    };
+
+    #[test]
+    fn intervals_surround_the_mean_of_the_samples() {
+        let interval = Interval::from_samples(&[2.0, 4.0, 6.0, 8.0]);
+
+        assert_eq!(interval.estimate, 5.0);
+        assert!(interval.low < 5.0 && interval.high > 5.0);
+        assert!((interval.high - 5.0 - 1.96 * (20.0f64 / 3.0 / 4.0).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn intervals_never_go_below_zero() {
+        let interval = Interval::from_samples(&[0.0, 0.0, 0.0, 100.0]);
+
+        assert_eq!(interval.low, 0.0);
+    }
+
+    #[test]
+    fn sampled_paths_estimate_the_size_of_the_real_search() {
+        let words = TEST_WORDS;
+        let (_, actual) = count_by_first_row(&words, &Constraints::<5>::default(), &|_| {});
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let estimate = estimate_search::<5>(&words, 20_000, Duration::ZERO, &mut rng);
+
+        let actual_nodes = actual.nodes as f64;
+        let actual_solutions = actual.solutions as f64;
+        assert!(
+            estimate.nodes.low <= actual_nodes && actual_nodes <= estimate.nodes.high,
+            "{actual_nodes} nodes isn't in {}",
+            estimate.nodes
+        );
+        assert!(
+            estimate.solutions.low <= actual_solutions
+                && actual_solutions <= estimate.solutions.high,
+            "{actual_solutions} solutions isn't in {}",
+            estimate.solutions
+        );
+        assert!(estimate.nodes_per_second > 0.0);
+    }
+}
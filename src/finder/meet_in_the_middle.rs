@@ -0,0 +1,401 @@
+//! A search that builds the top and bottom of the grid separately and joins them through the
+//! middle row, rather than going all the way down the tree from the first row.
+//!
+//! The top half is the rows above the middle one, stacked so every column reads as the start
+//! of some word. The bottom half is the rows below it, stacked so every column reads as the end
+//! of one, which is the same as a top half of the words spelt backwards. Bottom halves are
+//! indexed by the ends of all their columns together.
+//!
+//! Under a top half, each column only leaves the letters that come next in words starting with
+//! it, so middle rows are built a letter at a time from those rather than tried one by one.
+//! Once a middle row is in, the words that could go down each column give the ends a bottom
+//! half has to have, and the index picks out just the bottom halves with those ends.
+
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "multi-thread")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    Solution, SolutionFinder,
+    cancel::{CancelToken, NodeMeter},
+    letter_bit,
+};
+
+/// Finds every solution by joining the halves above and below the middle row
+pub struct MeetInTheMiddleFinder<'a, const N: usize = 5> {
+    words: Vec<&'a str>,
+    /// Every word with each letter in the same place, so a column can be looked up
+    lookup: HashMap<[u8; N], usize>,
+    /// The starts of words, down to and including the middle row
+    starts: HashSet<Vec<u8>>,
+    /// The letters that come next after each start of a word shorter than the word itself
+    following: HashMap<Vec<u8>, Vec<u8>>,
+    /// The words starting with each start as long as a top half and the middle row
+    by_start: HashMap<Vec<u8>, Vec<usize>>,
+    /// The ends of words written backwards, up to but not including the middle row
+    ends: HashSet<Vec<u8>>,
+}
+
+/// The bottom halves, listed top row first and indexed by the ends of their columns, written
+/// one after the other from the first column
+struct Bottoms {
+    by_ends: HashMap<Vec<u8>, Vec<Vec<usize>>>,
+    /// The ends of the first few columns of some bottom half, so a join can give up on a column
+    /// end that none of them carry on from
+    partial: HashSet<Vec<u8>>,
+}
+
+impl<const N: usize> MeetInTheMiddleFinder<'_, N> {
+    /// The row the halves are joined through
+    const MIDDLE: usize = N / 2;
+
+    /// How many letters of each column are below the middle row
+    const END: usize = N - Self::MIDDLE - 1;
+
+    fn bytes(&self, word: usize) -> &[u8] {
+        self.words[word].as_bytes()
+    }
+
+    /// Every stack of `height` different words whose columns, read from the first word, are
+    /// all in `fragments`, or `None` if `meter` stopped it
+    fn stacks(
+        &self,
+        height: usize,
+        fragments: &HashSet<Vec<u8>>,
+        meter: &mut NodeMeter,
+    ) -> Option<Vec<Vec<usize>>> {
+        let mut stacks = vec![vec![]];
+        let mut column = Vec::with_capacity(height);
+        for _ in 0..height {
+            let mut taller = vec![];
+            for stack in &stacks {
+                for word in 0..self.words.len() {
+                    if meter.tick() {
+                        return None;
+                    }
+                    if stack.contains(&word) {
+                        continue;
+                    }
+                    let fits = (0..N).all(|x| {
+                        column.clear();
+                        column.extend(stack.iter().map(|&row| self.bytes(row)[x]));
+                        column.push(self.bytes(word)[x]);
+                        fragments.contains(&column)
+                    });
+                    if fits {
+                        let mut stack = stack.clone();
+                        stack.push(word);
+                        taller.push(stack);
+                    }
+                }
+            }
+            stacks = taller;
+        }
+        Some(stacks)
+    }
+
+    /// Lists and indexes the bottom halves, or returns `None` if `meter` stopped it
+    fn bottoms(&self, meter: &mut NodeMeter) -> Option<Bottoms> {
+        let mut bottoms = Bottoms {
+            by_ends: HashMap::new(),
+            partial: HashSet::new(),
+        };
+        for mut stack in self.stacks(Self::END, &self.ends, meter)? {
+            stack.reverse();
+            let ends = (0..N)
+                .flat_map(|x| stack.iter().map(move |&row| (row, x)))
+                .map(|(row, x)| self.bytes(row)[x])
+                .collect::<Vec<_>>();
+            bottoms
+                .partial
+                .extend((1..N).map(|x| ends[..x * Self::END].to_vec()));
+            bottoms.by_ends.entry(ends).or_default().push(stack);
+        }
+        Some(bottoms)
+    }
+
+    /// Finds every solution with `top` above the middle row, or returns `false` if `meter`
+    /// stopped it
+    fn join(
+        &self,
+        top: &[usize],
+        bottoms: &Bottoms,
+        sink: &(dyn Fn(Solution<N>) + Sync),
+        meter: &mut NodeMeter,
+    ) -> bool {
+        let mut starts: [Vec<u8>; N] = std::array::from_fn(|x| {
+            top.iter()
+                .map(|&row| self.bytes(row)[x])
+                .collect::<Vec<_>>()
+        });
+        let mut allowed = [0; N];
+        for (allowed, start) in allowed.iter_mut().zip(&starts) {
+            let Some(next) = self.following.get(start) else {
+                return true;
+            };
+            *allowed = next
+                .iter()
+                .fold(0, |mask, &letter| mask | letter_bit(letter));
+        }
+
+        let mut rows = top.to_vec();
+        self.middle_rows(&allowed, &mut Vec::with_capacity(N), &mut |middle| {
+            if meter.tick() {
+                return false;
+            }
+            if top.contains(&middle) {
+                return true;
+            }
+            let columns = std::array::from_fn(|x| {
+                starts[x].push(self.bytes(middle)[x]);
+                let words = self.by_start.get(&starts[x]).map_or(&[][..], Vec::as_slice);
+                starts[x].pop();
+                words
+            });
+            rows.push(middle);
+            let finished =
+                self.join_bottoms(&columns, &mut vec![], &mut rows, bottoms, sink, meter);
+            rows.pop();
+            finished
+        })
+    }
+
+    /// Calls `each` with every word that has one of the `allowed` letters in each place and
+    /// starts with `row`, stopping if it returns `false`
+    fn middle_rows(
+        &self,
+        allowed: &[u32; N],
+        row: &mut Vec<u8>,
+        each: &mut dyn FnMut(usize) -> bool,
+    ) -> bool {
+        if row.len() == N {
+            let row: [u8; N] = row.as_slice().try_into().unwrap();
+            return self.lookup.get(&row).is_none_or(|&word| each(word));
+        }
+        let Some(next) = self.following.get(row) else {
+            return true;
+        };
+        for &letter in next {
+            if allowed[row.len()] & letter_bit(letter) == 0 {
+                continue;
+            }
+            row.push(letter);
+            let finished = self.middle_rows(allowed, row, each);
+            row.pop();
+            if !finished {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Puts every bottom half under `rows` whose columns finish one of the words in `columns`,
+    /// given the `ends` already picked for the first few columns, or returns `false` if `meter`
+    /// stopped it
+    fn join_bottoms(
+        &self,
+        columns: &[&[usize]; N],
+        ends: &mut Vec<u8>,
+        rows: &mut Vec<usize>,
+        bottoms: &Bottoms,
+        sink: &(dyn Fn(Solution<N>) + Sync),
+        meter: &mut NodeMeter,
+    ) -> bool {
+        let x = match Self::END {
+            0 => N,
+            end => ends.len() / end,
+        };
+        if x == N {
+            for bottom in bottoms.by_ends.get(ends).into_iter().flatten() {
+                if meter.tick() {
+                    return false;
+                }
+                rows.extend(bottom);
+                if let Some(sol) = self.solution(rows) {
+                    sink(sol);
+                }
+                rows.truncate(Self::MIDDLE + 1);
+            }
+            return true;
+        }
+        for &word in columns[x] {
+            if meter.tick() {
+                return false;
+            }
+            ends.extend(&self.bytes(word)[Self::MIDDLE + 1..]);
+            let stopped = (x + 1 == N || bottoms.partial.contains(ends))
+                && !self.join_bottoms(columns, ends, rows, bottoms, sink, meter);
+            ends.truncate(x * Self::END);
+            if stopped {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The solution with these rows, if every column is a word and no word is used twice
+    fn solution(&self, rows: &[usize]) -> Option<Solution<N>> {
+        let mut used = rows.to_vec();
+        for x in 0..N {
+            let column = std::array::from_fn(|y| self.bytes(rows[y])[x]);
+            let &word = self.lookup.get(&column)?;
+            if used.contains(&word) {
+                return None;
+            }
+            used.push(word);
+        }
+        if (1..N).any(|y| rows[..y].contains(&rows[y])) {
+            return None;
+        }
+        Some(Solution::new(std::array::from_fn(|y| self.words[rows[y]])))
+    }
+}
+
+impl<'a, const N: usize> SolutionFinder<'a, N> for MeetInTheMiddleFinder<'a, N> {
+    fn new(words: &'a [&'a str]) -> Self {
+        let words = words
+            .iter()
+            .copied()
+            .filter(|word| word.len() == N && word.is_ascii())
+            .collect::<Vec<_>>();
+        let middle = Self::MIDDLE;
+        let mut lookup = HashMap::new();
+        let mut starts = HashSet::new();
+        let mut following = HashMap::<_, Vec<_>>::new();
+        let mut by_start = HashMap::<_, Vec<_>>::new();
+        let mut ends = HashSet::new();
+        for (i, word) in words.iter().enumerate() {
+            let bytes = word.as_bytes();
+            lookup.entry(bytes.try_into().unwrap()).or_insert(i);
+            starts.extend((1..=middle + 1).map(|len| bytes[..len].to_vec()));
+            for len in 0..N {
+                let next = following.entry(bytes[..len].to_vec()).or_default();
+                if !next.contains(&bytes[len]) {
+                    next.push(bytes[len]);
+                }
+            }
+            by_start
+                .entry(bytes[..=middle].to_vec())
+                .or_default()
+                .push(i);
+            let backwards = bytes.iter().rev().copied().collect::<Vec<_>>();
+            ends.extend((1..N - middle).map(|len| backwards[..len].to_vec()));
+        }
+        Self {
+            words,
+            lookup,
+            starts,
+            following,
+            by_start,
+            ends,
+        }
+    }
+
+    fn find(&self) -> Vec<Solution<N>> {
+        let sols = std::sync::Mutex::new(vec![]);
+        self.find_each(&|sol| sols.lock().unwrap().push(sol));
+        sols.into_inner().unwrap()
+    }
+
+    fn find_each_until(&self, sink: &(dyn Fn(Solution<N>) + Sync), token: &CancelToken) -> bool {
+        let mut meter = NodeMeter::new(token);
+        let Some(tops) = self.stacks(Self::MIDDLE, &self.starts, &mut meter) else {
+            return false;
+        };
+        let Some(bottoms) = self.bottoms(&mut meter) else {
+            return false;
+        };
+        drop(meter);
+
+        let search = |top: &Vec<usize>| self.join(top, &bottoms, sink, &mut NodeMeter::new(token));
+
+        #[cfg(feature = "multi-thread")]
+        {
+            tops.par_iter().all(search)
+        }
+
+        #[cfg(not(feature = "multi-thread"))]
+        {
+            tops.iter().all(search)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::finder::{TEST_WORDS, TrivialFinder, new_double_sided, sorted};
+    use test::Bencher;
+    extern crate test;
+
+    #[test]
+    fn finds_the_same_solutions_as_the_double_sided_search() {
+        let expected = sorted(
+            new_double_sided::solutions(&TEST_WORDS)
+                .into_iter()
+                .map(Solution::new),
+        );
+
+        assert_eq!(
+            sorted(MeetInTheMiddleFinder::<5>::new(&TEST_WORDS).find()),
+            expected
+        );
+        assert_eq!(expected.len(), 6);
+    }
+
+    #[test]
+    fn joins_halves_of_other_sized_squares() {
+        let three = [
+            "abc", "def", "ghi", "adg", "beh", "cfi", "abd", "ceg", "hid",
+        ];
+        let four = [
+            "abcd", "efgh", "ijkl", "mnop", "aeim", "bfjn", "cgko", "dhlp", "abce", "mnoq",
+        ];
+
+        assert_eq!(
+            sorted(MeetInTheMiddleFinder::<3>::new(&three).find()),
+            sorted(TrivialFinder::<3>::new(&three).find())
+        );
+        assert_eq!(
+            sorted(MeetInTheMiddleFinder::<4>::new(&four).find()),
+            sorted(TrivialFinder::<4>::new(&four).find())
+        );
+    }
+
+    #[test]
+    fn skips_grids_that_use_a_word_twice() {
+        let words = ["aba", "bab"];
+
+        assert!(MeetInTheMiddleFinder::<3>::new(&words).find().is_empty());
+        assert!(TrivialFinder::<3>::new(&words).find().is_empty());
+    }
+
+    /// Enough words for the two searches to take noticeably different times
+    const BENCH_WORDS: usize = 1000;
+
+    fn most_common(count: usize) -> Vec<&'static str> {
+        include_str!("../../words.txt")
+            .lines()
+            .filter(|word| word.len() == 5)
+            .take(count)
+            .collect()
+    }
+
+    #[bench]
+    #[ignore = "bench"]
+    fn time_meet_in_the_middle(b: &mut Bencher) {
+        let words = most_common(BENCH_WORDS);
+        let finder = MeetInTheMiddleFinder::<5>::new(&words);
+
+        b.iter(|| finder.find());
+    }
+
+    #[bench]
+    #[ignore = "bench"]
+    fn time_new_double_sided(b: &mut Bencher) {
+        let words = most_common(BENCH_WORDS);
+
+        b.iter(|| new_double_sided::solutions(&words));
+    }
+}
@@ -7,12 +7,22 @@ use itertools::Itertools;
 use crate::{Solution, Word};
 
 pub mod double_sided;
+pub mod estimate;
+pub mod meet_in_the_middle;
+pub mod most_constrained;
 pub mod new_double_sided;
+pub mod rectangle_finder;
+pub mod registry;
 pub mod top_down_finder;
 pub mod trivial_finder;
 
 pub use double_sided::*;
+pub use estimate::*;
+pub use meet_in_the_middle::*;
+pub use most_constrained::*;
 pub use new_double_sided::*;
+pub use rectangle_finder::*;
+pub use registry::*;
 pub use top_down_finder::*;
 pub use trivial_finder::*;
 
@@ -24,20 +34,20 @@ pub enum LetterPlayed {
     AllUsed,
 }
 
-pub struct Puzzle {
-    solution: Solution,
-    guesses: Vec<Word>,
+pub struct Puzzle<const N: usize = 5> {
+    solution: Solution<N>,
+    guesses: Vec<Word<N>>,
 }
 
-impl Puzzle {
-    pub fn new(solution: Solution) -> Self {
+impl<const N: usize> Puzzle<N> {
+    pub fn new(solution: Solution<N>) -> Self {
         Self {
             solution,
             guesses: Vec::new(),
         }
     }
 
-    pub fn view(&self) -> PuzzleViewModel {
+    pub fn view(&self) -> PuzzleViewModel<N> {
         let grid = self.grid();
         let hints = self.hints(&grid);
         let alphabet = self.alphabet(&hints);
@@ -50,18 +60,18 @@ impl Puzzle {
         }
     }
 
-    fn guesses(&self) -> Vec<Word> {
+    fn guesses(&self) -> Vec<Word<N>> {
         self.guesses.clone()
     }
 
-    fn is_finished(&self, grid: &[[Option<AsciiChar>; 5]; 5]) -> bool {
+    fn is_finished(&self, grid: &[[Option<AsciiChar>; N]; N]) -> bool {
         grid.iter()
             .flat_map(|row| row.iter())
             .all(|ch| ch.is_some())
     }
 
-    fn grid(&self) -> [[Option<AsciiChar>; 5]; 5] {
-        let mut arr = [[None; 5]; 5];
+    fn grid(&self) -> [[Option<AsciiChar>; N]; N] {
+        let mut arr = [[None; N]; N];
         for (y, row) in self.solution.rows.iter().enumerate() {
             for (x, ch) in row.0.into_iter().enumerate() {
                 for word in &self.guesses {
@@ -74,15 +84,10 @@ impl Puzzle {
         arr
     }
 
-    fn hints(&self, grid: &[[Option<AsciiChar>; 5]; 5]) -> [RowHint; 5] {
-        [0, 1, 2, 3, 4]
-            .iter()
-            .zip(self.solution.rows.clone())
-            // is there a better way to do something like array.enumerate here?
-            .map(|(i, word)| row_hint(word, grid[*i], self.guesses.clone()))
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+    fn hints(&self, grid: &[[Option<AsciiChar>; N]; N]) -> [RowHint; N] {
+        std::array::from_fn(|i| {
+            row_hint(self.solution.rows[i].clone(), grid[i], self.guesses.clone())
+        })
     }
 
     fn alphabet(&self, hints: &[RowHint]) -> BTreeMap<AsciiChar, LetterPlayed> {
@@ -117,7 +122,7 @@ impl Puzzle {
         )
     }
 
-    pub fn guess(&mut self, guess: Word) {
+    pub fn guess(&mut self, guess: Word<N>) {
         self.guesses.push(guess);
     }
 }
@@ -133,6 +138,11 @@ impl RowHint {
         self.0.clone().into()
     }
 
+    /// The same letters in alphabetical order
+    pub fn sorted(&self) -> Self {
+        self.letters().into_iter().sorted().collect()
+    }
+
     pub fn is_equivalent_to(&self, other: &Self) -> bool {
         self.0.len() == other.0.len()
             && self
@@ -154,7 +164,11 @@ impl From<&str> for RowHint {
     }
 }
 
-fn row_hint(row: Word, known_letters: [Option<AsciiChar>; 5], guesses: Vec<Word>) -> RowHint {
+fn row_hint<const N: usize>(
+    row: Word<N>,
+    known_letters: [Option<AsciiChar>; N],
+    guesses: Vec<Word<N>>,
+) -> RowHint {
     let possible_hints = row
         .0
         .into_iter()
@@ -174,26 +188,61 @@ fn row_hint(row: Word, known_letters: [Option<AsciiChar>; 5], guesses: Vec<Word>
         .collect()
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default, PartialOrd, Ord, Hash)]
-pub struct PuzzleViewModel {
-    pub guesses: Vec<Word>,
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PuzzleViewModel<const N: usize = 5> {
+    pub guesses: Vec<Word<N>>,
     pub is_finished: bool,
-    pub grid: [[Option<AsciiChar>; 5]; 5],
-    pub hints: [RowHint; 5],
+    pub grid: [[Option<AsciiChar>; N]; N],
+    pub hints: [RowHint; N],
     pub alphabet: BTreeMap<AsciiChar, LetterPlayed>,
 }
 
-impl PuzzleViewModel {
+impl<const N: usize> Default for PuzzleViewModel<N> {
+    fn default() -> Self {
+        Self {
+            guesses: Vec::new(),
+            is_finished: false,
+            grid: [[None; N]; N],
+            hints: std::array::from_fn(|_| RowHint::default()),
+            alphabet: BTreeMap::new(),
+        }
+    }
+}
+
+impl<const N: usize> PuzzleViewModel<N> {
+    /// The same view with each row's hint in alphabetical order, so views giving the same
+    /// feedback are equal and hash the same
+    pub fn normalized(&self) -> Self {
+        Self {
+            hints: self.hints.each_ref().map(RowHint::sorted),
+            ..self.clone()
+        }
+    }
+
+    /// Whether two views give the same feedback: the same guesses, squares, alphabet and the
+    /// same letters hinted at in each row, in any order
     pub fn is_equivalent_to(&self, other: &Self) -> bool {
-        self.grid == other.grid
-            && self
-                .hints
-                .iter()
-                .zip(other.hints.iter())
-                .all(|(s, o)| s.is_equivalent_to(o))
+        self.normalized() == other.normalized()
     }
 }
 
+/// A small word list with a handful of solutions, for tests that run a whole search
+#[cfg(test)]
+pub(crate) const TEST_WORDS: [&str; 30] = [
+    "grime", "honor", "outdo", "steed", "terse", "ghost", "route", "inter", "modes", "erode",
+    "level", "oxide", "atria", "truck", "hasty", "loath", "extra", "virus", "edict", "leaky",
+    "loses", "apple", "diode", "lured", "emery", "ladle", "opium", "spore", "elder", "seedy",
+];
+
+/// `sols` written out and sorted, so searches finding them in different orders compare equal
+#[cfg(test)]
+pub(crate) fn sorted<const N: usize>(sols: impl IntoIterator<Item = Solution<N>>) -> Vec<String> {
+    sols.into_iter()
+        .map(|sol| sol.to_string())
+        .sorted()
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use crate::{BinSearchRange, SolutionFinder};
@@ -437,7 +486,7 @@ mod test {
             "cdcbf", "gbadf", "fadfb", "fbbdf", "dccac", "bfcfa", "fccgb", "adcbf", "cccbc",
             "aaaaa",
         ];
-        let f = TrivialFinder::new(&input);
+        let f = TrivialFinder::<5>::new(&input);
         let sols = new_double_sided::solutions(&input);
         println!("{}", sols.iter().map(|sol| sol.join("\n")).join("\n\n"));
         assert_eq!(f.find().len(), sols.len());
@@ -449,11 +498,15 @@ mod test {
             "aaaaa", "gfgdg", "fefgc", "cfbff", "ffefa", "ffcdd", "gfegf", "ebfcb", "gcfff",
             "fabgd",
         ];
-        let f = TrivialFinder::new(&input);
+        let f = TrivialFinder::<5>::new(&input);
         let double = DoubleSidedFinder::<BinSearchRange>::new(&input);
         let sols = new_double_sided::solutions(&input);
 
         assert_eq!(f.find().len(), sols.len());
+        assert_eq!(
+            MeetInTheMiddleFinder::<5>::new(&input).find().len(),
+            sols.len()
+        );
         let double_sols = double.find();
         for sol in double_sols.iter() {
             println!("Solution is:\n{sol}");
@@ -467,11 +520,15 @@ mod test {
             "cdcbf", "gbadf", "fadfb", "fbbdf", "dccac", "bfcfa", "fccgb", "adcbf", "cccbc",
             "aaaaa",
         ];
-        let oracle = TrivialFinder::new(&input);
+        let oracle = TrivialFinder::<5>::new(&input);
         let double = DoubleSidedFinder::<BinSearchRange>::new(&input);
         let sols = new_double_sided::solutions(&input);
 
         assert_eq!(oracle.find().len(), sols.len());
+        assert_eq!(
+            MeetInTheMiddleFinder::<5>::new(&input).find().len(),
+            sols.len()
+        );
         let double_sols = double.find();
         for sol in double_sols.iter() {
             println!("Solution is:\n{sol}");
@@ -479,17 +536,85 @@ mod test {
         assert_eq!(double_sols.len(), sols.len());
     }
 
+    #[test]
+    fn streaming_gives_the_same_solutions_as_collecting() {
+        let input = [
+            "grime", "honor", "outdo", "steed", "terse", "ghost", "route", "inter", "modes",
+            "erode",
+        ];
+        let double = DoubleSidedFinder::<BinSearchRange>::new(&input);
+        let streamed = std::sync::Mutex::new(vec![]);
+        double.find_each(&|sol| streamed.lock().unwrap().push(sol));
+        let mut streamed = streamed.into_inner().unwrap();
+        let mut collected = double.find();
+        streamed.sort();
+        collected.sort();
+        assert_eq!(streamed, collected);
+
+        let streamed = std::sync::Mutex::new(vec![]);
+        new_double_sided::for_each_solution(
+            &input,
+            &new_double_sided::Constraints::default(),
+            &|sol| streamed.lock().unwrap().push(sol),
+            &|_| {},
+        );
+        let mut streamed = streamed.into_inner().unwrap();
+        let mut collected = new_double_sided::solutions(&input);
+        streamed.sort();
+        collected.sort();
+        assert_eq!(streamed, collected);
+    }
+
+    #[test]
+    fn finds_squares_of_other_sizes() {
+        let three = [
+            "abc", "def", "ghi", "adg", "beh", "cfi", "abd", "ceg", "hid",
+        ];
+        let four = [
+            "abcd", "efgh", "ijkl", "mnop", "aeim", "bfjn", "cgko", "dhlp", "abce", "mnoq",
+        ];
+
+        let mut oracle = TrivialFinder::<3>::new(&three)
+            .find()
+            .into_iter()
+            .map(|sol| sol.to_string())
+            .collect_vec();
+        let mut found = new_double_sided::solutions_of_size::<3>(&three)
+            .into_iter()
+            .map(|sol| sol.join(","))
+            .collect_vec();
+        oracle.sort();
+        found.sort();
+        assert_eq!(found, ["abc,def,ghi", "adg,beh,cfi"]);
+        assert_eq!(found, oracle);
+
+        let mut oracle = TrivialFinder::<4>::new(&four)
+            .find()
+            .into_iter()
+            .map(|sol| sol.to_string())
+            .collect_vec();
+        let mut found = new_double_sided::solutions_of_size::<4>(&four)
+            .into_iter()
+            .map(|sol| sol.join(","))
+            .collect_vec();
+        oracle.sort();
+        found.sort();
+        assert_eq!(found, ["abcd,efgh,ijkl,mnop", "aeim,bfjn,cgko,dhlp"]);
+        assert_eq!(found, oracle);
+    }
+
     #[test]
     fn simple_case_4() {
         let input = [
             "faecc", "ccaff", "eebaf", "bbebc", "fbece", "cbdfe", "eafed", "egbda", "ccafd",
             "abgca",
         ];
-        let oracle = TrivialFinder::new(&input);
+        let oracle = TrivialFinder::<5>::new(&input);
         let double = DoubleSidedFinder::<BinSearchRange>::new(&input);
         let new_sols = new_double_sided::solutions(&input);
         let oracle_sols = oracle.find();
         let old_sols = double.find();
+        let halves_sols = MeetInTheMiddleFinder::<5>::new(&input).find();
 
         for sol in new_sols.iter() {
             println!("New solver: {}", sol.join(","));
@@ -502,5 +627,6 @@ mod test {
         }
         assert_eq!(oracle_sols.len(), new_sols.len());
         assert_eq!(old_sols.len(), new_sols.len());
+        assert_eq!(halves_sols.len(), new_sols.len());
     }
 }
@@ -0,0 +1,300 @@
+//! A search that fills whichever row or column has the fewest words left that fit it, rather
+//! than going through them in a fixed order.
+//!
+//! Since any slot can be filled next, the letters already known in a slot needn't be a prefix,
+//! so instead of prefix ranges each slot keeps the set of words that still fit it. Placing a
+//! word narrows the sets of the slots crossing it to the words with the right letter where they
+//! cross, and a slot whose set runs out ends the branch.
+
+#[cfg(feature = "multi-thread")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    Solution, SolutionFinder,
+    cancel::{CancelToken, NodeMeter},
+    template::Template,
+};
+
+/// A set of word indexes, one bit per word
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Words(Vec<u64>);
+
+impl Words {
+    fn none(len: usize) -> Self {
+        Self(vec![0; len.div_ceil(64)])
+    }
+
+    fn all(len: usize) -> Self {
+        let mut words = Self::none(len);
+        (0..len).for_each(|i| words.insert(i));
+        words
+    }
+
+    fn insert(&mut self, i: usize) {
+        self.0[i / 64] |= 1 << (i % 64);
+    }
+
+    fn remove(&mut self, i: usize) {
+        self.0[i / 64] &= !(1 << (i % 64));
+    }
+
+    fn keep_only(&mut self, other: &Self) {
+        self.0
+            .iter_mut()
+            .zip(other.0.iter())
+            .for_each(|(block, other)| *block &= other);
+    }
+
+    fn len(&self) -> u32 {
+        self.0.iter().map(|block| block.count_ones()).sum()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &block)| {
+            (0..64)
+                .filter(move |bit| block & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+/// Fills the grid one row or column at a time, always picking the one with the fewest words
+/// left that fit it. Slots are numbered with the rows first, then the columns.
+pub struct MostConstrainedFinder<'a, const N: usize = 5> {
+    words: Vec<&'a str>,
+    /// The words with each letter at each position, indexed by position then letter
+    with_letter: Vec<[Words; 26]>,
+    template: Template<N>,
+}
+
+impl<const N: usize> MostConstrainedFinder<'_, N> {
+    /// Only finds solutions with `template`'s letters, which the search starts from rather
+    /// than checking at the end
+    pub fn with_template(self, template: Template<N>) -> Self {
+        Self { template, ..self }
+    }
+
+    /// The words that fit each slot before anything is placed
+    fn starting_slots(&self, template: &Template<N>) -> Vec<Words> {
+        let all = Words::all(self.words.len());
+        (0..2 * N)
+            .map(|slot| {
+                let mut fits = all.clone();
+                for i in 0..N {
+                    let (y, x) = square(slot, i, N);
+                    if let Some(letter) = template.letter(y, x) {
+                        fits.keep_only(&self.with_letter[i][letter_index(letter.as_byte())]);
+                    }
+                }
+                fits
+            })
+            .collect()
+    }
+
+    fn search(
+        &self,
+        template: &Template<N>,
+        sink: &(dyn Fn(Solution<N>) + Sync),
+        token: &CancelToken,
+    ) -> bool {
+        let slots = self.starting_slots(template);
+        let placed = vec![None; 2 * N];
+        let Some(first) = most_constrained(&placed, &slots) else {
+            return true;
+        };
+        let branches = slots[first].iter().collect::<Vec<_>>();
+
+        let search = |&word: &usize| {
+            let mut search = Search {
+                finder: self,
+                placed: placed.clone(),
+                meter: NodeMeter::new(token),
+                sink,
+            };
+            search.place(first, word, &slots);
+            !search.meter.stopped()
+        };
+
+        #[cfg(feature = "multi-thread")]
+        {
+            branches.par_iter().all(search)
+        }
+
+        #[cfg(not(feature = "multi-thread"))]
+        {
+            branches.iter().all(search)
+        }
+    }
+}
+
+impl<'a, const N: usize> SolutionFinder<'a, N> for MostConstrainedFinder<'a, N> {
+    fn new(words: &'a [&'a str]) -> Self {
+        let words = words
+            .iter()
+            .copied()
+            .filter(|word| word.len() == N && word.bytes().all(|b| b.is_ascii_lowercase()))
+            .collect::<Vec<_>>();
+        let mut with_letter = vec![std::array::from_fn(|_| Words::none(words.len())); N];
+        for (i, word) in words.iter().enumerate() {
+            for (position, letter) in word.bytes().enumerate() {
+                with_letter[position][letter_index(letter)].insert(i);
+            }
+        }
+        Self {
+            words,
+            with_letter,
+            template: Template::default(),
+        }
+    }
+
+    fn find(&self) -> Vec<Solution<N>> {
+        let sols = std::sync::Mutex::new(vec![]);
+        self.find_each(&|sol| sols.lock().unwrap().push(sol));
+        sols.into_inner().unwrap()
+    }
+
+    fn find_each_until(&self, sink: &(dyn Fn(Solution<N>) + Sync), token: &CancelToken) -> bool {
+        self.search(&self.template, sink, token)
+    }
+
+    fn find_matching(&self, template: &Template<N>) -> Vec<Solution<N>> {
+        let sols = std::sync::Mutex::new(vec![]);
+        self.search(
+            template,
+            &|sol| sols.lock().unwrap().push(sol),
+            &CancelToken::new(),
+        );
+        sols.into_inner().unwrap()
+    }
+}
+
+fn letter_index(letter: u8) -> usize {
+    (letter - b'a') as usize
+}
+
+/// The row and column of the `i`th square along `slot`
+fn square(slot: usize, i: usize, n: usize) -> (usize, usize) {
+    match slot < n {
+        true => (slot, i),
+        false => (i, slot - n),
+    }
+}
+
+/// The empty slot with the fewest words left that fit it, or `None` once every slot is filled
+fn most_constrained(placed: &[Option<usize>], slots: &[Words]) -> Option<usize> {
+    (0..slots.len())
+        .filter(|&slot| placed[slot].is_none())
+        .min_by_key(|&slot| slots[slot].len())
+}
+
+struct Search<'f, 'a, 't, const N: usize> {
+    finder: &'f MostConstrainedFinder<'a, N>,
+    /// The word in each slot, if it's been filled
+    placed: Vec<Option<usize>>,
+    meter: NodeMeter<'t>,
+    sink: &'f (dyn Fn(Solution<N>) + Sync),
+}
+
+impl<const N: usize> Search<'_, '_, '_, N> {
+    /// Puts `word` in `slot` and searches everything under it, given the words that fit each
+    /// slot beforehand
+    fn place(&mut self, slot: usize, word: usize, slots: &[Words]) {
+        if self.meter.tick() {
+            return;
+        }
+        let letters = self.finder.words[word].as_bytes();
+        let mut next = slots.to_vec();
+        for (other, fits) in next.iter_mut().enumerate() {
+            if self.placed[other].is_some() || other == slot {
+                continue;
+            }
+            fits.remove(word);
+            // Rows only cross columns and the other way round, at the square with the
+            // row's index in the column and the column's index in the row
+            if (other < N) != (slot < N) {
+                let (crossing, position) = match slot < N {
+                    true => (other - N, slot),
+                    false => (other, slot - N),
+                };
+                fits.keep_only(&self.finder.with_letter[position][letter_index(letters[crossing])]);
+            }
+        }
+
+        self.placed[slot] = Some(word);
+        self.fill(&next);
+        self.placed[slot] = None;
+    }
+
+    fn fill(&mut self, slots: &[Words]) {
+        let Some(slot) = most_constrained(&self.placed, slots) else {
+            let rows = std::array::from_fn(|y| self.finder.words[self.placed[y].unwrap()]);
+            (self.sink)(Solution::new(rows));
+            return;
+        };
+        for word in slots[slot].iter() {
+            if self.meter.stopped() {
+                return;
+            }
+            self.place(slot, word, slots);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::finder::{TEST_WORDS, new_double_sided, sorted};
+
+    #[test]
+    fn finds_the_same_solutions_as_the_double_sided_search() {
+        let finder = MostConstrainedFinder::<5>::new(&TEST_WORDS);
+        let expected = sorted(
+            new_double_sided::solutions(&TEST_WORDS)
+                .into_iter()
+                .map(Solution::new),
+        );
+
+        assert_eq!(sorted(finder.find()), expected);
+        assert_eq!(expected.len(), 6);
+    }
+
+    #[test]
+    fn starts_from_the_template_letters() {
+        let template: Template = "?????/?????/?????/?????/????y".parse().unwrap();
+        let finder = MostConstrainedFinder::<5>::new(&TEST_WORDS);
+        let expected = sorted(
+            finder
+                .find()
+                .into_iter()
+                .filter(|sol| template.matches(sol)),
+        );
+
+        assert!(!expected.is_empty());
+        assert_eq!(sorted(finder.find_matching(&template)), expected);
+        assert_eq!(
+            sorted(
+                MostConstrainedFinder::<5>::new(&TEST_WORDS)
+                    .with_template(template)
+                    .find()
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn picks_the_slot_with_the_fewest_words_left() {
+        let template: Template = "?????/?????/?????/?????/l????".parse().unwrap();
+        let finder = MostConstrainedFinder::<5>::new(&TEST_WORDS);
+        let mut placed = vec![None; 10];
+        let slots = finder.starting_slots(&template);
+
+        // Six words start with an l for the last row, but only "level" ends in one for the
+        // first column
+        assert_eq!(slots[4].len(), 6);
+        assert_eq!(slots[5].len(), 1);
+        assert_eq!(most_constrained(&placed, &slots), Some(5));
+
+        placed[5] = Some(0);
+        assert_eq!(most_constrained(&placed, &slots), Some(4));
+    }
+}
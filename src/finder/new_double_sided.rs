@@ -2,22 +2,45 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::{Debug, Display},
     hash::RandomState,
-    ops::{Deref, DerefMut},
+    iter::Sum,
+    ops::{Add, Deref, DerefMut},
+    sync::Mutex,
+    time::{Duration, Instant},
 };
 
 use itertools::Itertools;
+use rand::{Rng, seq::SliceRandom};
+#[cfg(feature = "multi-thread")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    DuplicatePolicy, FirstRowFinder, Solution, SolutionCounts, SolutionFinder,
+    cancel::{CancelToken, NodeMeter},
+    finder::estimate::TreeSample,
+    letter_bit,
+    progress::{Progress, ProgressTracker},
+    rules::{LetterRules, WordRules},
+    shard::Shard,
+    template::Template,
+};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 struct Letter(u8);
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
-struct Word([u8; 5]);
-#[derive(Clone, PartialEq, Eq, Default)]
-struct Grid([[u8; 5]; 5]);
+struct Word<const N: usize = 5>([u8; N]);
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Grid<const N: usize = 5>([[u8; N]; N]);
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct WordFrag<'a>(&'a [u8]);
 
-impl Grid {
-    fn place_row(&mut self, row: Word, index: usize) {
+impl<const N: usize> Default for Grid<N> {
+    fn default() -> Self {
+        Grid([[0; N]; N])
+    }
+}
+
+impl<const N: usize> Grid<N> {
+    fn place_row(&mut self, row: Word<N>, index: usize) {
         for x in 0..index {
             debug_assert!(
                 self[index][x] == row[x],
@@ -27,46 +50,56 @@ impl Grid {
         self[index] = *row;
     }
 
-    fn place_col(&mut self, col: Word, index: usize) {
+    fn place_col(&mut self, col: Word<N>, index: usize) {
         for y in 0..index {
             debug_assert!(
                 self[y][index] == col[y],
                 "Tried placing {col} in \n{self}at col {index}"
             );
         }
-        for y in index..5 {
+        for y in index..N {
             self[y][index] = col[y];
         }
     }
 
     fn remove_row(&mut self, index: usize) {
-        for x in index..5 {
+        for x in index..N {
             self[index][x] = 0;
         }
     }
 
     fn remove_col(&mut self, index: usize) {
-        for y in (index + 1)..5 {
+        for y in (index + 1)..N {
             self[y][index] = 0;
         }
     }
 
-    fn word_at_col(&self, index: usize) -> Word {
-        let mut word = [0; 5];
-        for y in 0..5 {
+    fn word_at_col(&self, index: usize) -> Word<N> {
+        let mut word = [0; N];
+        for y in 0..N {
             word[y] = self[y][index];
         }
         Word(word)
     }
 
-    fn word_at_row(&self, index: usize) -> Word {
+    fn word_at_row(&self, index: usize) -> Word<N> {
         Word(self[index])
     }
 
+    /// Treating this grid's non-zero letters as fixed, whether `word` can go in row `index`
+    fn fits_row(&self, index: usize, word: &Word<N>) -> bool {
+        fits(&self[index], word)
+    }
+
+    /// Treating this grid's non-zero letters as fixed, whether `word` can go in column `index`
+    fn fits_col(&self, index: usize, word: &Word<N>) -> bool {
+        fits(&self.word_at_col(index), word)
+    }
+
     fn transpose(&self) -> Self {
         let mut t = Self::default();
-        for x in 0..5 {
-            for y in 0..5 {
+        for x in 0..N {
+            for y in 0..N {
                 t[x][y] = self[y][x];
             }
         }
@@ -84,14 +117,20 @@ impl Display for Letter {
         Display::fmt(&str::from_utf8(&[self.0]).unwrap_or(" "), f)
     }
 }
-impl Debug for Word {
+impl<const N: usize> Debug for Word<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Debug::fmt(str::from_utf8(self.0.as_slice()).unwrap_or("     "), f)
+        Debug::fmt(
+            str::from_utf8(self.0.as_slice()).unwrap_or(&" ".repeat(N)),
+            f,
+        )
     }
 }
-impl Display for Word {
+impl<const N: usize> Display for Word<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(str::from_utf8(self.0.as_slice()).unwrap_or("     "), f)
+        Display::fmt(
+            str::from_utf8(self.0.as_slice()).unwrap_or(&" ".repeat(N)),
+            f,
+        )
     }
 }
 
@@ -102,12 +141,12 @@ impl Display for WordFrag<'_> {
     }
 }
 
-impl Debug for Grid {
+impl<const N: usize> Debug for Grid<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("Grid").field(&self.0).finish()
     }
 }
-impl Display for Grid {
+impl<const N: usize> Display for Grid<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for row in self.0 {
             Display::fmt(&Word(row), f)?;
@@ -117,14 +156,14 @@ impl Display for Grid {
     }
 }
 
-impl From<[[u8; 5]; 5]> for Grid {
-    fn from(value: [[u8; 5]; 5]) -> Self {
+impl<const N: usize> From<[[u8; N]; N]> for Grid<N> {
+    fn from(value: [[u8; N]; N]) -> Self {
         Grid(value)
     }
 }
 
-impl From<[u8; 5]> for Word {
-    fn from(value: [u8; 5]) -> Self {
+impl<const N: usize> From<[u8; N]> for Word<N> {
+    fn from(value: [u8; N]) -> Self {
         Word(value)
     }
 }
@@ -134,25 +173,20 @@ impl From<u8> for Letter {
     }
 }
 
-impl<'a> TryFrom<&'a [u8]> for WordFrag<'a> {
-    type Error = ();
-    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
-        if value.len() > 5 {
-            Err(())
-        } else {
-            Ok(WordFrag(value))
-        }
+impl<'a> From<&'a [u8]> for WordFrag<'a> {
+    fn from(value: &'a [u8]) -> Self {
+        WordFrag(value)
     }
 }
 
-impl<'a> From<&'a Word> for WordFrag<'a> {
-    fn from(value: &'a Word) -> Self {
+impl<'a, const N: usize> From<&'a Word<N>> for WordFrag<'a> {
+    fn from(value: &'a Word<N>) -> Self {
         WordFrag(value.as_slice())
     }
 }
 
-impl<'a> From<&'a [u8; 5]> for WordFrag<'a> {
-    fn from(value: &'a [u8; 5]) -> Self {
+impl<'a, const N: usize> From<&'a [u8; N]> for WordFrag<'a> {
+    fn from(value: &'a [u8; N]) -> Self {
         WordFrag(value)
     }
 }
@@ -177,28 +211,33 @@ impl Deref for Letter {
     }
 }
 
-impl Deref for Word {
-    type Target = [u8; 5];
+impl<const N: usize> Deref for Word<N> {
+    type Target = [u8; N];
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl Deref for Grid {
-    type Target = [[u8; 5]; 5];
+impl<const N: usize> Deref for Grid<N> {
+    type Target = [[u8; N]; N];
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl DerefMut for Grid {
+impl<const N: usize> DerefMut for Grid<N> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.0
     }
 }
 pub fn solutions<'a>(words: &[&'a str]) -> Vec<[&'a str; 5]> {
+    solutions_of_size(words)
+}
+
+/// Finds the `N`×`N` squares made from `words`, which should all be `N` letters long
+pub fn solutions_of_size<'a, const N: usize>(words: &[&'a str]) -> Vec<[&'a str; N]> {
     let word_bytes = convert(words);
 
     let starting_cache = starting_letters_cache(&word_bytes);
@@ -207,195 +246,1106 @@ pub fn solutions<'a>(words: &[&'a str]) -> Vec<[&'a str; 5]> {
     convert_sols(words, sols)
 }
 
-fn convert_sols<'a>(words: &[&'a str], sols: Vec<Grid>) -> Vec<[&'a str; 5]> {
-    let pairs: HashMap<WordFrag<'a>, &&str, RandomState> =
-        HashMap::from_iter(words.iter().map(|w| (WordFrag(w.as_bytes()), w)));
-    sols.iter()
-        .map(|sol| sol.map(|a| *pairs[&WordFrag(a.as_slice())]))
+/// What a search has to respect beyond every row and column being a word
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Constraints<const N: usize = 5> {
+    pub template: Template<N>,
+    pub duplicates: DuplicatePolicy,
+    /// Which first rows to search, all of them unless the search is split up
+    pub shard: Shard,
+    /// Only hand on the canonical form of each solution, unless its transpose doesn't fit the
+    /// template
+    pub canonical: bool,
+    /// Words every solution has to have, and words none of them can use
+    pub rules: WordRules,
+    /// How often letters can repeat, and letters every solution has to have or can't have
+    pub letters: LetterRules,
+}
+
+/// The words `constraints` lets a search use
+fn allowed_words<'a, const N: usize>(
+    words: &[&'a str],
+    constraints: &Constraints<N>,
+) -> Vec<&'a str> {
+    words
+        .iter()
+        .copied()
+        .filter(|word| constraints.rules.allows(word) && constraints.letters.allows(word))
         .collect()
 }
 
-fn convert(words: &[&str]) -> Vec<Word> {
+/// The first rows whose branches `shard` searches, each with the word it came from
+fn first_rows<'w, 'a, const N: usize>(
+    words: &'w [&'a str],
+    word_bytes: &'w [Word<N>],
+    shard: Shard,
+) -> Vec<(&'w &'a str, &'w Word<N>)> {
+    words
+        .iter()
+        .zip(word_bytes)
+        .enumerate()
+        .filter(|(i, _)| shard.contains(*i))
+        .map(|(_, branch)| branch)
+        .collect_vec()
+}
+
+/// Finds only the solutions with `template`'s letters, pruning with them during the search
+pub fn solutions_matching<'a, const N: usize>(
+    words: &[&'a str],
+    template: &Template<N>,
+) -> Vec<[&'a str; N]> {
+    let constraints = Constraints {
+        template: *template,
+        ..Default::default()
+    };
+    solutions_with(words, &constraints)
+}
+
+pub fn solutions_with<'a, const N: usize>(
+    words: &[&'a str],
+    constraints: &Constraints<N>,
+) -> Vec<[&'a str; N]> {
+    let found = Mutex::new(Vec::new());
+    for_each_solution(
+        words,
+        constraints,
+        &|sol| found.lock().unwrap().push(sol),
+        &|_| {},
+    );
+    found.into_inner().unwrap()
+}
+
+/// Hands each solution meeting `constraints` to `sink` as soon as it's found instead of
+/// collecting them all first, and tells `observer` whenever a first row's branch is done
+pub fn for_each_solution<'a, const N: usize>(
+    words: &[&'a str],
+    constraints: &Constraints<N>,
+    sink: &(dyn Fn([&'a str; N]) + Sync),
+    observer: &(dyn Fn(Progress) + Sync),
+) -> SearchStats {
+    for_each_solution_until(words, constraints, sink, observer, &CancelToken::new())
+}
+
+/// Like [`for_each_solution`], giving up once `token` stops the search. The stats say whether
+/// it did, and `observer` only hears about the branches that were searched to the end.
+pub fn for_each_solution_until<'a, const N: usize>(
+    words: &[&'a str],
+    constraints: &Constraints<N>,
+    sink: &(dyn Fn([&'a str; N]) + Sync),
+    observer: &(dyn Fn(Progress) + Sync),
+    token: &CancelToken,
+) -> SearchStats {
+    let words = &allowed_words(words, constraints);
+    let word_bytes = convert(words);
+    let dictionary = Dictionary::new(starting_letters_cache(&word_bytes));
+    let pairs = word_lookup(words);
+    let branches = first_rows(words, &word_bytes, constraints.shard);
+    let tracker = ProgressTracker::new(branches.len());
+
+    let search = |&(_, &row_word): &(&&'a str, &Word<N>)| {
+        let mut emit = |grid: &Grid<N>| sink(convert_grid(&pairs, grid));
+        let stats = search_first_row(
+            &dictionary,
+            constraints,
+            row_word,
+            Some(&mut emit),
+            Some(token),
+        );
+        if !stats.stopped {
+            observer(tracker.finish_branch(stats.solutions, stats.nodes));
+        }
+        stats
+    };
+
+    #[cfg(feature = "multi-thread")]
+    {
+        branches.par_iter().map(search).sum()
+    }
+
+    #[cfg(not(feature = "multi-thread"))]
+    {
+        branches.iter().map(search).sum()
+    }
+}
+
+/// Counts the solutions meeting `constraints` under each first row without building any of them
+pub fn count_by_first_row<const N: usize>(
+    words: &[&str],
+    constraints: &Constraints<N>,
+    observer: &(dyn Fn(Progress) + Sync),
+) -> (SolutionCounts, SearchStats) {
+    let words = &allowed_words(words, constraints);
+    let word_bytes = convert(words);
+    let dictionary = Dictionary::new(starting_letters_cache(&word_bytes));
+    let branches = first_rows(words, &word_bytes, constraints.shard);
+    let tracker = ProgressTracker::new(branches.len());
+
+    let count = |&(&first_row, &row_word): &(&&str, &Word<N>)| {
+        let stats = search_first_row(&dictionary, constraints, row_word, None, None);
+        observer(tracker.finish_branch(stats.solutions, stats.nodes));
+        (first_row.to_string(), stats)
+    };
+
+    #[cfg(feature = "multi-thread")]
+    let branches = branches.par_iter().map(count).collect::<Vec<_>>();
+
+    #[cfg(not(feature = "multi-thread"))]
+    let branches = branches.iter().map(count).collect_vec();
+
+    let stats = branches.iter().map(|(_, stats)| *stats).sum();
+    let counts = branches
+        .into_iter()
+        .map(|(word, stats)| (word, stats.solutions))
+        .collect();
+    (counts, stats)
+}
+
+/// Searches one first-row word at a time so a caller can record progress between branches.
+///
+/// Branches whose first row `is_finished` accepts are skipped, and `on_finished` is handed
+/// every solution found under a first row once that branch has been fully searched.
+/// `observer` only counts the branches that weren't already finished.
+pub fn solutions_by_first_row<'a, const N: usize>(
+    words: &[&'a str],
+    constraints: &Constraints<N>,
+    is_finished: &(dyn Fn(&str) -> bool + Sync),
+    on_finished: &(dyn Fn(&'a str, Vec<[&'a str; N]>) + Sync),
+    observer: &(dyn Fn(Progress) + Sync),
+) -> SearchStats {
+    let words = &allowed_words(words, constraints);
+    let word_bytes = convert(words);
+    let dictionary = Dictionary::new(starting_letters_cache(&word_bytes));
+    let branches = first_rows(words, &word_bytes, constraints.shard)
+        .into_iter()
+        .filter(|(first_row, _)| !is_finished(first_row))
+        .collect_vec();
+    let tracker = ProgressTracker::new(branches.len());
+
+    let search = |&(&first_row, &row_word): &(&&'a str, &Word<N>)| {
+        let (sols, stats) = branch_solutions(&dictionary, constraints, row_word);
+        on_finished(first_row, convert_sols(words, sols));
+        observer(tracker.finish_branch(stats.solutions, stats.nodes));
+        stats
+    };
+
+    #[cfg(feature = "multi-thread")]
+    {
+        branches.par_iter().map(search).sum()
+    }
+
+    #[cfg(not(feature = "multi-thread"))]
+    {
+        branches.iter().map(search).sum()
+    }
+}
+
+/// This module's search as a [`SolutionFinder`], taking first rows in the order the words were
+/// given
+#[derive(Debug, Clone)]
+pub struct NewDoubleSidedFinder<'a, const N: usize = 5> {
+    words: &'a [&'a str],
+    constraints: Constraints<N>,
+}
+
+impl<'a, const N: usize> NewDoubleSidedFinder<'a, N> {
+    pub fn with_constraints(self, constraints: Constraints<N>) -> Self {
+        Self {
+            constraints,
+            ..self
+        }
+    }
+}
+
+impl<'a, const N: usize> SolutionFinder<'a, N> for NewDoubleSidedFinder<'a, N> {
+    fn new(words: &'a [&'a str]) -> Self {
+        Self {
+            words,
+            constraints: Constraints::default(),
+        }
+    }
+
+    fn find(&self) -> Vec<Solution<N>> {
+        solutions_with(self.words, &self.constraints)
+            .into_iter()
+            .map(Solution::new)
+            .collect()
+    }
+
+    fn find_each_until(&self, sink: &(dyn Fn(Solution<N>) + Sync), token: &CancelToken) -> bool {
+        let stats = for_each_solution_until(
+            self.words,
+            &self.constraints,
+            &|sol| sink(Solution::new(sol)),
+            &|_| {},
+            token,
+        );
+        !stats.stopped
+    }
+
+    fn find_matching(&self, template: &Template<N>) -> Vec<Solution<N>> {
+        let constraints = Constraints {
+            template: *template,
+            ..self.constraints.clone()
+        };
+        self.clone().with_constraints(constraints).find()
+    }
+}
+
+impl<const N: usize> FirstRowFinder<N> for NewDoubleSidedFinder<'_, N> {
+    fn find_each_observed(
+        &self,
+        sink: &(dyn Fn(Solution<N>) + Sync),
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> SearchStats {
+        for_each_solution(
+            self.words,
+            &self.constraints,
+            &|sol| sink(Solution::new(sol)),
+            observer,
+        )
+    }
+
+    fn count_by_first_row(
+        &self,
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> (SolutionCounts, SearchStats) {
+        count_by_first_row(self.words, &self.constraints, observer)
+    }
+
+    fn find_by_first_row(
+        &self,
+        is_finished: &(dyn Fn(&str) -> bool + Sync),
+        on_finished: &(dyn Fn(&str, Vec<Solution<N>>) + Sync),
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> SearchStats {
+        solutions_by_first_row(
+            self.words,
+            &self.constraints,
+            is_finished,
+            &|word, sols| on_finished(word, sols.into_iter().map(Solution::new).collect()),
+            observer,
+        )
+    }
+}
+
+/// How many of the last pairs of words a tree sample searches in full rather than sampling.
+/// Any more and each sample costs a good slice of the whole search.
+const SEARCHED_IN_FULL: usize = 2;
+
+/// Follows `samples` random paths down the tree a full search of `words` would explore, each
+/// giving an estimate of the whole tree's size
+pub(crate) fn sample_tree<const N: usize>(
+    words: &[&str],
+    samples: usize,
+    rng: &mut impl Rng,
+) -> Vec<TreeSample> {
+    let word_bytes = convert(words);
+    let dictionary = Dictionary::<N>::new(starting_letters_cache(&word_bytes));
+
+    (0..samples)
+        .map(|_| {
+            let mut sample = TreeSample::default();
+            Search::new(&dictionary, None).sample_pair_of_words(0, 1.0, rng, &mut sample);
+            sample
+        })
+        .collect()
+}
+
+/// Searches the branches under randomly picked first rows until `budget` runs out, stopping
+/// part way through a branch if need be, and returns how many nodes they placed and how long
+/// that took on one thread
+pub(crate) fn time_branches<const N: usize>(
+    words: &[&str],
+    budget: Duration,
+    rng: &mut impl Rng,
+) -> (u64, Duration) {
+    let word_bytes = convert(words);
+    let dictionary = Dictionary::<N>::new(starting_letters_cache(&word_bytes));
+
+    let started = Instant::now();
+    let token = CancelToken::new().with_deadline(started + budget);
+    let mut nodes = 0;
+    for &row_word in word_bytes.choose_multiple(rng, word_bytes.len()) {
+        let stats = search_first_row(
+            &dictionary,
+            &Constraints::default(),
+            row_word,
+            None,
+            Some(&token),
+        );
+        nodes += stats.nodes;
+        if stats.stopped {
+            break;
+        }
+    }
+    (nodes, started.elapsed())
+}
+
+/// How much work a search did, and how often each check cut a branch short
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Rows and columns placed into the grid
+    pub nodes: u64,
+    /// Placements undone because a crossing row or column stopped being the start of any word
+    pub pruned_by_prefix: u64,
+    /// Placements undone because some empty cell had no letter left that both its row and
+    /// column could use
+    pub pruned_by_mask: u64,
+    /// Placements undone because some required word no longer had anywhere to go
+    pub pruned_by_rules: u64,
+    /// Placements undone because a letter repeated too often or a required letter had nowhere
+    /// left to go
+    pub pruned_by_letters: u64,
+    pub solutions: u64,
+    /// Whether a [`CancelToken`] stopped the search before it got to the end
+    pub stopped: bool,
+}
+
+impl Add for SearchStats {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            nodes: self.nodes + rhs.nodes,
+            pruned_by_prefix: self.pruned_by_prefix + rhs.pruned_by_prefix,
+            pruned_by_mask: self.pruned_by_mask + rhs.pruned_by_mask,
+            pruned_by_rules: self.pruned_by_rules + rhs.pruned_by_rules,
+            pruned_by_letters: self.pruned_by_letters + rhs.pruned_by_letters,
+            solutions: self.solutions + rhs.solutions,
+            stopped: self.stopped || rhs.stopped,
+        }
+    }
+}
+
+impl Sum for SearchStats {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl Display for SearchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent = |n: u64| 100.0 * n as f64 / self.nodes.max(1) as f64;
+        writeln!(f, "nodes:                  {}", self.nodes)?;
+        writeln!(
+            f,
+            "pruned by prefix:       {} ({:.1}%)",
+            self.pruned_by_prefix,
+            percent(self.pruned_by_prefix)
+        )?;
+        writeln!(
+            f,
+            "pruned by letter masks: {} ({:.1}%)",
+            self.pruned_by_mask,
+            percent(self.pruned_by_mask)
+        )?;
+        writeln!(
+            f,
+            "pruned by word rules:   {} ({:.1}%)",
+            self.pruned_by_rules,
+            percent(self.pruned_by_rules)
+        )?;
+        writeln!(
+            f,
+            "pruned by letter rules: {} ({:.1}%)",
+            self.pruned_by_letters,
+            percent(self.pruned_by_letters)
+        )?;
+        writeln!(f, "solutions:              {}", self.solutions)?;
+        if self.stopped {
+            writeln!(f, "stopped early")?;
+        }
+        Ok(())
+    }
+}
+
+fn convert_sols<'a, const N: usize>(words: &[&'a str], sols: Vec<Grid<N>>) -> Vec<[&'a str; N]> {
+    let pairs = word_lookup(words);
+    sols.iter().map(|sol| convert_grid(&pairs, sol)).collect()
+}
+
+fn word_lookup<'a>(words: &[&'a str]) -> HashMap<WordFrag<'a>, &'a str, RandomState> {
+    HashMap::from_iter(words.iter().map(|w| (WordFrag(w.as_bytes()), *w)))
+}
+
+fn convert_grid<'a, const N: usize>(
+    pairs: &HashMap<WordFrag<'a>, &'a str>,
+    sol: &Grid<N>,
+) -> [&'a str; N] {
+    sol.map(|a| pairs[&WordFrag(a.as_slice())])
+}
+
+fn convert<const N: usize>(words: &[&str]) -> Vec<Word<N>> {
     words
         .iter()
         .map(|w| Word(w.as_bytes().try_into().unwrap()))
         .collect()
 }
 
-fn starting_letters_cache(words: &[Word]) -> HashMap<WordFrag<'_>, Vec<Word>> {
-    let mut cache = HashMap::<WordFrag<'_>, Vec<Word>>::new();
+fn starting_letters_cache<const N: usize>(
+    words: &[Word<N>],
+) -> HashMap<WordFrag<'_>, Vec<Word<N>>> {
+    let mut cache = HashMap::<WordFrag<'_>, Vec<Word<N>>>::new();
     cache.insert(WordFrag(&[]), words.to_vec());
     for word in words {
-        for i in 1..=5 {
+        for i in 1..=N {
             let w = WordFrag(&word[0..i]);
             cache
                 .entry(w)
-                .and_modify(|e: &mut Vec<Word>| e.push(*word))
+                .and_modify(|e: &mut Vec<Word<N>>| e.push(*word))
                 .or_insert(vec![*word]);
         }
     }
     cache
 }
 
-fn find_solutions(cache: HashMap<WordFrag<'_>, Vec<Word>>) -> Vec<Grid> {
-    let mut placed_words = HashSet::new();
-    let mut solution = Grid::default();
+/// The prefix tables a search reads from, shared by every branch and thread
+struct Dictionary<'w, const N: usize = 5> {
+    cache: HashMap<WordFrag<'w>, Vec<Word<N>>>,
+    /// For each prefix, a bitmask per position of the letters its words have there
+    masks: HashMap<WordFrag<'w>, [u32; N]>,
+}
 
-    let original_solution = solution.clone();
-    let solutions = place_pair_of_words(&cache, &mut placed_words, &mut solution, 0);
-    assert_eq!(
-        original_solution, solution,
-        "sent:\n{original_solution}but got back:\n{solution}"
-    );
-    solutions
+impl<'w, const N: usize> Dictionary<'w, N> {
+    fn new(cache: HashMap<WordFrag<'w>, Vec<Word<N>>>) -> Self {
+        let masks = cache
+            .iter()
+            .map(|(frag, words)| {
+                let mut mask = [0; N];
+                for word in words {
+                    for (x, letter) in word.iter().enumerate() {
+                        mask[x] |= letter_bit(*letter);
+                    }
+                }
+                (frag.clone(), mask)
+            })
+            .collect();
+        Self { cache, masks }
+    }
+}
+
+/// Splits the search by first row, so each branch can run on its own thread with its own grid
+fn find_solutions<const N: usize>(cache: HashMap<WordFrag<'_>, Vec<Word<N>>>) -> Vec<Grid<N>> {
+    let dictionary = Dictionary::new(cache);
+    let first_rows = &dictionary.cache[&WordFrag(&[])];
+
+    #[cfg(feature = "multi-thread")]
+    {
+        first_rows
+            .par_iter()
+            .flat_map_iter(|&row_word| {
+                branch_solutions(&dictionary, &Constraints::default(), row_word).0
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "multi-thread"))]
+    {
+        first_rows
+            .iter()
+            .flat_map(|&row_word| {
+                branch_solutions(&dictionary, &Constraints::default(), row_word).0
+            })
+            .collect()
+    }
+}
+
+fn branch_solutions<const N: usize>(
+    dictionary: &Dictionary<'_, N>,
+    constraints: &Constraints<N>,
+    row_word: Word<N>,
+) -> (Vec<Grid<N>>, SearchStats) {
+    let mut solutions = Vec::new();
+    let mut collect = |grid: &Grid<N>| solutions.push(grid.clone());
+    let stats = search_first_row(dictionary, constraints, row_word, Some(&mut collect), None);
+    (solutions, stats)
+}
+
+/// Finds every solution meeting `constraints` whose search branch starts with `row_word`.
+///
+/// A search only builds grids whose first row sorts before their first column, and hands on
+/// their transposes as well. When the template or word rules aren't symmetric that transpose
+/// might not fit them, so a second pass searches with them transposed and hands on only the
+/// transposes.
+/// Symmetric squares are their own transposes, so they only ever need the one pass.
+fn search_first_row<const N: usize>(
+    dictionary: &Dictionary<'_, N>,
+    constraints: &Constraints<N>,
+    row_word: Word<N>,
+    mut sink: Option<&mut dyn FnMut(&Grid<N>)>,
+    token: Option<&CancelToken>,
+) -> SearchStats {
+    let fixed = fixed_letters(&constraints.template);
+    let rules = &constraints.rules;
+    let transposed_rules = &rules.transpose();
+    if constraints.duplicates == DuplicatePolicy::Symmetric {
+        let mut search = Search::new(dictionary, reborrow(&mut sink))
+            .matching(fixed, Emit::Grid, constraints)
+            .with_rules(rules, transposed_rules)
+            .until(token);
+        search.place_first_symmetric_row(row_word);
+        return search.finished_stats();
+    }
+
+    let transposed = fixed.transpose();
+    let passes = if fixed == transposed && rules.is_symmetric() {
+        vec![(fixed, rules, transposed_rules, Emit::Both)]
+    } else {
+        vec![
+            (fixed, rules, transposed_rules, Emit::Grid),
+            (transposed, transposed_rules, rules, Emit::Transpose),
+        ]
+    };
+
+    passes
+        .into_iter()
+        .map(|(fixed, rules, other_rules, emit)| {
+            let mut search = Search::new(dictionary, reborrow(&mut sink))
+                .matching(fixed, emit, constraints)
+                .with_rules(rules, other_rules)
+                .until(token);
+            search.place_first_row(row_word);
+            search.finished_stats()
+        })
+        .sum()
+}
+
+/// Lends out the sink for as long as one search lasts, so the next pass can have it after
+fn reborrow<'s, const N: usize>(
+    sink: &'s mut Option<&mut dyn FnMut(&Grid<N>)>,
+) -> Option<&'s mut dyn FnMut(&Grid<N>)> {
+    sink.as_mut()
+        .map(|sink| &mut **sink as &mut dyn FnMut(&Grid<N>))
+}
+
+/// The template's fixed letters, with 0 in the squares that can be anything
+fn fixed_letters<const N: usize>(template: &Template<N>) -> Grid<N> {
+    Grid(std::array::from_fn(|y| {
+        std::array::from_fn(|x| template.letter(y, x).map_or(0, |letter| letter.as_byte()))
+    }))
+}
+
+fn fits<const N: usize>(fixed: &[u8; N], word: &Word<N>) -> bool {
+    fixed
+        .iter()
+        .zip(word.iter())
+        .all(|(&fixed, &letter)| fixed == 0 || fixed == letter)
 }
 
-fn place_pair_of_words(
-    cache: &HashMap<WordFrag<'_>, Vec<Word>>,
-    placed_words: &mut HashSet<Word>,
-    solution: &mut Grid,
-    index: usize,
-) -> Vec<Grid> {
-    assert!(index < 5);
-    for x in index..5 {
-        for y in index..5 {
-            debug_assert!(solution[y][x] == 0, "{solution}was not empty at {y},{x}");
+/// Which of each grid it finds, and that grid's transpose, a search hands to its sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Emit {
+    Both,
+    Grid,
+    Transpose,
+}
+
+/// What a search without any word rules checks against
+static NO_RULES: WordRules = WordRules::NONE;
+
+/// The state of one depth-first search, which counts every finished grid and hands it to `sink`
+/// if there is one
+struct Search<'c, 'w, const N: usize = 5> {
+    dictionary: &'c Dictionary<'w, N>,
+    placed_words: HashSet<Word<N>>,
+    solution: Grid<N>,
+    /// Letters the solution has to have, with 0 for squares that can be anything
+    fixed: Grid<N>,
+    /// Words the grid has to have, as they apply to the grid this search builds
+    rules: &'c WordRules,
+    /// The same rules for the transpose of the grid this search builds
+    transposed_rules: &'c WordRules,
+    letters: LetterRules,
+    emit: Emit,
+    duplicates: DuplicatePolicy,
+    canonical: bool,
+    stats: SearchStats,
+    sink: Option<&'c mut dyn FnMut(&Grid<N>)>,
+    /// Checks in with the search's [`CancelToken`], if it has one
+    meter: Option<NodeMeter<'c>>,
+}
+
+impl<'c, 'w, const N: usize> Search<'c, 'w, N> {
+    fn new(dictionary: &'c Dictionary<'w, N>, sink: Option<&'c mut dyn FnMut(&Grid<N>)>) -> Self {
+        Self {
+            dictionary,
+            placed_words: HashSet::new(),
+            solution: Grid::default(),
+            fixed: Grid::default(),
+            rules: &NO_RULES,
+            transposed_rules: &NO_RULES,
+            letters: LetterRules::default(),
+            emit: Emit::Both,
+            duplicates: DuplicatePolicy::Distinct,
+            canonical: false,
+            stats: SearchStats::default(),
+            sink,
+            meter: None,
         }
     }
-    for x in 0..index {
-        for y in 0..5 {
-            debug_assert!(
-                solution[y][x] != 0,
-                "{solution}should have been empty at {x},{y}"
-            );
-            debug_assert!(
-                solution[x][y] != 0,
-                "{solution}should have been empty at {y},{x}"
-            );
+
+    fn matching(self, fixed: Grid<N>, emit: Emit, constraints: &Constraints<N>) -> Self {
+        Self {
+            fixed,
+            emit,
+            duplicates: constraints.duplicates,
+            canonical: constraints.canonical,
+            letters: constraints.letters,
+            ..self
+        }
+    }
+
+    /// Only finds grids meeting `rules`, cutting off branches with nowhere left for a required
+    /// word
+    fn with_rules(self, rules: &'c WordRules, transposed_rules: &'c WordRules) -> Self {
+        Self {
+            rules,
+            transposed_rules,
+            ..self
+        }
+    }
+
+    /// Gives up once `token` stops the search, leaving the grid as empty as it started
+    fn until(self, token: Option<&'c CancelToken>) -> Self {
+        Self {
+            meter: token.map(NodeMeter::new),
+            ..self
+        }
+    }
+
+    /// Counts one more word placed
+    fn count_node(&mut self) {
+        self.stats.nodes += 1;
+        if let Some(meter) = self.meter.as_mut() {
+            meter.tick();
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        self.meter.as_ref().is_some_and(NodeMeter::stopped)
+    }
+
+    fn finished_stats(&self) -> SearchStats {
+        SearchStats {
+            stopped: self.stopped(),
+            ..self.stats
+        }
+    }
+
+    /// Counts the finished grid, or its transpose, and hands it on if anything is listening
+    fn emit(&mut self, transpose: bool) {
+        if !self.fits_rules() || !self.fits_letters(0) {
+            return;
+        }
+        if self.canonical && !self.is_canonical(transpose) {
+            return;
+        }
+        self.stats.solutions += 1;
+        match self.sink.as_mut() {
+            Some(sink) if transpose => sink(&self.solution.transpose()),
+            Some(sink) => sink(&self.solution),
+            None => {}
+        }
+    }
+
+    /// Whether the grid about to be handed on, or its transpose, should stand for the pair.
+    ///
+    /// The other one of the pair only counts if it fits the template and rules too. Whichever
+    /// of the two this search has in its grid, that's the case when the grid fits the
+    /// transposed template and rules.
+    fn is_canonical(&self, transpose: bool) -> bool {
+        let (grid, other) = match transpose {
+            true => (self.solution.transpose(), self.solution.clone()),
+            false => (self.solution.clone(), self.solution.transpose()),
+        };
+        let fixed = self.fixed.transpose();
+        let solution = &self.solution;
+        let other_fits = (0..N).all(|y| fits(&fixed[y], &solution.word_at_row(y)))
+            && self
+                .transposed_rules
+                .could_hold::<N>(|y, x| Some(solution[y][x]));
+        grid <= other || !other_fits
+    }
+
+    /// Whether `word` can't go in the grid because it's already there
+    fn is_used(&self, word: &Word<N>) -> bool {
+        self.duplicates != DuplicatePolicy::Any && self.placed_words.contains(word)
+    }
+
+    /// Finds every classic word square with `row_word` as its first row. Each row is also the
+    /// matching column, so only `N` words need placing.
+    fn place_first_symmetric_row(&mut self, row_word: Word<N>) {
+        self.place_symmetric_word(0, row_word);
+        assert_eq!(
+            Grid::default(),
+            self.solution,
+            "sent an empty grid but got back:\n{}",
+            self.solution
+        );
+    }
+
+    fn place_symmetric_row(&mut self, index: usize) {
+        if index == N {
+            self.emit(false);
+            return;
         }
+
+        let prefix = self.solution.word_at_row(index);
+        let Some(words) = self.dictionary.cache.get(&to_slice(&prefix)) else {
+            return;
+        };
+        for word in words {
+            if self.stopped() {
+                break;
+            }
+            self.place_symmetric_word(index, *word);
+        }
+    }
+
+    fn place_symmetric_word(&mut self, index: usize, word: Word<N>) {
+        if !self.fixed.fits_row(index, &word)
+            || !self.fixed.fits_col(index, &word)
+            || self.is_used(&word)
+        {
+            return;
+        }
+        self.solution.place_row(word, index);
+        self.solution.place_col(word, index);
+        self.placed_words.insert(word);
+        self.count_node();
+
+        if self.forward_check(index + 1, index + 1) {
+            self.place_symmetric_row(index + 1);
+        }
+
+        self.placed_words.remove(&word);
+        self.solution.remove_row(index);
+        self.solution.remove_col(index);
     }
 
-    if index == 4 {
-        let original_solution = solution.clone();
-        let solutions = place_last_letter(cache, placed_words, solution);
-        debug_assert_eq!(
-            original_solution, *solution,
-            "sent:\n{original_solution}but got back:\n{solution}"
+    /// Finds every solution whose search branch starts with `row_word` as the first row
+    fn place_first_row(&mut self, row_word: Word<N>) {
+        self.place_row_word(0, row_word);
+        self.solution.remove_row(0);
+        assert_eq!(
+            Grid::default(),
+            self.solution,
+            "sent an empty grid but got back:\n{}",
+            self.solution
         );
-        return solutions;
     }
 
-    // println!("Starting at {index} with:\n{solution}\n-----");
-    let binding = solution.word_at_row(index);
-    let current_row = to_slice(&binding);
-    let words = match cache.get(&current_row) {
-        Some(w) => w,
-        None => return Vec::new(),
-    };
+    fn place_pair_of_words(&mut self, index: usize) {
+        assert!(index < N);
+        let solution = &self.solution;
+        for x in index..N {
+            for y in index..N {
+                debug_assert!(solution[y][x] == 0, "{solution}was not empty at {y},{x}");
+            }
+        }
+        for x in 0..index {
+            for y in 0..N {
+                debug_assert!(
+                    solution[y][x] != 0,
+                    "{solution}should have been empty at {x},{y}"
+                );
+                debug_assert!(
+                    solution[x][y] != 0,
+                    "{solution}should have been empty at {y},{x}"
+                );
+            }
+        }
 
-    let mut solutions = Vec::new();
-    for row_word in words {
-        if placed_words.contains(row_word) {
+        if index == N - 1 {
+            let original_solution = self.solution.clone();
+            self.place_last_letter();
+            debug_assert_eq!(
+                original_solution, self.solution,
+                "sent:\n{original_solution}but got back:\n{}",
+                self.solution
+            );
+            return;
+        }
+
+        // println!("Starting at {index} with:\n{solution}\n-----");
+        let binding = self.solution.word_at_row(index);
+        let current_row = to_slice(&binding);
+        let words = match self.dictionary.cache.get(&current_row) {
+            Some(w) => w,
+            None => return,
+        };
+
+        for row_word in words {
+            if self.stopped() {
+                break;
+            }
+            self.place_row_word(index, *row_word);
+        }
+        self.solution.remove_row(index);
+    }
+
+    fn place_row_word(&mut self, index: usize, row_word: Word<N>) {
+        if !self.fixed.fits_row(index, &row_word) {
+            return;
+        }
+        if self.is_used(&row_word) {
             // println!("Solution already contains {word}");
-            continue;
+            return;
         }
-        solution.place_row(*row_word, index);
-        placed_words.insert(*row_word);
+        self.solution.place_row(row_word, index);
+        self.placed_words.insert(row_word);
+        self.count_node();
         // println!("Placed {word} at row {index}:\n{solution}\n-----");
-        if !((index)..5).all(|i| {
-            let col = solution.word_at_col(i);
-            cache.get(&to_slice(&col)).is_some()
-        }) {
-            placed_words.remove(row_word);
-            continue;
+        if !self.forward_check(index + 1, index) {
+            self.placed_words.remove(&row_word);
+            return;
         }
 
-        let col = solution.word_at_col(index);
+        let col = self.solution.word_at_col(index);
         let empty_vec = Vec::new();
+        let cache = &self.dictionary.cache;
         let possible_columns = cache.get(&to_slice(&col)).unwrap_or(&empty_vec);
 
         for col_word in possible_columns {
-            if index == 0 && row_word > col_word {
+            if self.stopped() {
+                break;
+            }
+            if index == 0 && row_word > *col_word {
+                continue;
+            }
+            if !self.fixed.fits_col(index, col_word) {
                 continue;
             }
-            if placed_words.contains(col_word) {
+            if self.is_used(col_word) {
                 // println!("Solution already contains {w}");
                 continue;
             }
-            placed_words.insert(*col_word);
-            solution.place_col(*col_word, index);
+            self.placed_words.insert(*col_word);
+            self.solution.place_col(*col_word, index);
+            self.count_node();
 
             // println!("Placed {w} at col {index}:\n{solution}\n-----");
 
-            if !((index + 1)..5).all(|i| {
-                let row = solution.word_at_row(i);
-                cache.get(&to_slice(&row)).is_some()
-            }) {
-                placed_words.remove(col_word);
+            if !self.forward_check(index + 1, index + 1) {
+                self.placed_words.remove(col_word);
                 continue;
             }
 
-            let original_solution = solution.clone();
-            let mut new_solutions = place_pair_of_words(cache, placed_words, solution, index + 1);
+            let original_solution = self.solution.clone();
+            self.place_pair_of_words(index + 1);
             debug_assert_eq!(
-                original_solution, *solution,
-                "sent:\n{original_solution}but got back:\n{solution}"
+                original_solution, self.solution,
+                "sent:\n{original_solution}but got back:\n{}",
+                self.solution
             );
 
-            solutions.append(&mut new_solutions);
+            self.placed_words.remove(col_word);
+        }
+        self.placed_words.remove(&row_word);
+        self.solution.remove_col(index);
+    }
 
-            placed_words.remove(col_word);
+    /// Follows one random path down the tree `place_pair_of_words` would search from `index`.
+    ///
+    /// This is Knuth's estimator: each node stands in for all its siblings, so it's weighted by
+    /// the product of the branching factors on the way down to it, and adding up those weights
+    /// gives an unbiased estimate of how many nodes and solutions the whole tree has. Solutions
+    /// are too rare for a single path to land on one, so the last couple of rows and columns
+    /// are searched in full and their counts weighted instead. On big word lists most paths
+    /// still die out before then, so the solution estimate needs a lot more samples than the
+    /// node estimate does.
+    fn sample_pair_of_words(
+        &mut self,
+        index: usize,
+        weight: f64,
+        rng: &mut impl Rng,
+        sample: &mut TreeSample,
+    ) {
+        if index + SEARCHED_IN_FULL >= N {
+            let before = self.stats;
+            self.place_pair_of_words(index);
+            sample.nodes += weight * (self.stats.nodes - before.nodes) as f64;
+            sample.solutions += weight * (self.stats.solutions - before.solutions) as f64;
+            return;
         }
-        placed_words.remove(row_word);
-        solution.remove_col(index);
+
+        let prefix = self.solution.word_at_row(index);
+        let rows = self
+            .dictionary
+            .cache
+            .get(&to_slice(&prefix))
+            .into_iter()
+            .flatten()
+            .filter(|word| self.fixed.fits_row(index, word) && !self.is_used(word))
+            .collect_vec();
+        let Some(&&row_word) = rows.choose(rng) else {
+            return;
+        };
+        let weight = weight * rows.len() as f64;
+        sample.nodes += weight;
+        self.solution.place_row(row_word, index);
+        self.placed_words.insert(row_word);
+
+        if self.forward_check(index + 1, index) {
+            let col = self.solution.word_at_col(index);
+            let cols = self
+                .dictionary
+                .cache
+                .get(&to_slice(&col))
+                .into_iter()
+                .flatten()
+                .filter(|word| !(index == 0 && row_word > **word))
+                .filter(|word| self.fixed.fits_col(index, word) && !self.is_used(word))
+                .collect_vec();
+            if let Some(&&col_word) = cols.choose(rng) {
+                let weight = weight * cols.len() as f64;
+                sample.nodes += weight;
+                self.solution.place_col(col_word, index);
+                self.placed_words.insert(col_word);
+                if self.forward_check(index + 1, index + 1) {
+                    self.sample_pair_of_words(index + 1, weight, rng, sample);
+                }
+                self.placed_words.remove(&col_word);
+            }
+            self.solution.remove_col(index);
+        }
+
+        self.placed_words.remove(&row_word);
+        self.solution.remove_row(index);
     }
-    solution.remove_row(index);
-    solutions
-}
 
-fn place_last_letter(
-    cache: &HashMap<WordFrag<'_>, Vec<Word>>,
-    placed_words: &HashSet<Word>,
-    solution: &mut Grid,
-) -> Vec<Grid> {
-    let row = to_slice(&solution[4]);
-    let col_word = solution.word_at_col(4);
-    let col = to_slice(&col_word);
+    fn place_last_letter(&mut self) {
+        let last = N - 1;
+        let row = to_slice(&self.solution[last]);
+        let col_word = self.solution.word_at_col(last);
+        let col = to_slice(&col_word);
 
-    if row == col {
-        return Vec::new();
+        if row == col && self.duplicates != DuplicatePolicy::Any {
+            return;
+        }
+
+        let row_words = match self.dictionary.cache.get(&row) {
+            Some(v) => v,
+            None => return,
+        };
+        let row_letters: HashSet<u8, RandomState> = HashSet::from_iter(
+            row_words
+                .iter()
+                .filter(|w| !self.is_used(w))
+                .map(|w| w[last]),
+        );
+
+        let col_words = match self.dictionary.cache.get(&col) {
+            Some(k) => k,
+            None => return,
+        };
+        let col_letters = HashSet::from_iter(
+            col_words
+                .iter()
+                .filter(|w| !self.is_used(w))
+                .map(|w| w[last]),
+        );
+        // A grid whose first row is also its first column has its transpose found on a
+        // branch of its own, so only the grid itself gets handed on
+        let mirrored = self.solution.word_at_row(0) == self.solution.word_at_col(0);
+
+        let fixed = self.fixed[last][last];
+        let letters = row_letters
+            .intersection(&col_letters)
+            .filter(|&&letter| fixed == 0 || fixed == letter);
+        // println!("Found letters {:?}", letters.clone().collect_vec());
+        for letter in letters {
+            self.solution[last][last] = *letter;
+            if self.emit != Emit::Transpose {
+                self.emit(false);
+            }
+            if self.emit != Emit::Grid && !mirrored {
+                self.emit(true);
+            }
+        }
+        self.solution[last][last] = 0;
     }
 
-    let row_words = match cache.get(&row) {
-        Some(v) => v,
-        None => return Vec::new(),
-    };
-    let row_words_binding: HashSet<Word, _> = HashSet::from_iter(row_words.iter().copied());
-    let row_letters: HashSet<u8, RandomState> =
-        HashSet::from_iter(row_words_binding.difference(placed_words).map(|w| w[4]));
+    /// The letters the template allows in a square, as a mask
+    fn allowed(&self, y: usize, x: usize) -> u32 {
+        match self.fixed[y][x] {
+            0 => u32::MAX,
+            fixed => letter_bit(fixed),
+        }
+    }
 
-    let col_words = match cache.get(&col) {
-        Some(k) => k,
-        None => return Vec::new(),
-    };
-    let col_words_binding = HashSet::from_iter(col_words.iter().copied());
-    let col_letters = HashSet::from_iter(col_words_binding.difference(placed_words).map(|w| w[4]));
+    /// Checks every empty cell from `first_open_row` and `first_open_col` on still has a letter
+    /// that both its row's prefix and its column's prefix allow, and that the grid can still meet
+    /// its word and letter rules, counting what cut it off if not
+    fn forward_check(&mut self, first_open_row: usize, first_open_col: usize) -> bool {
+        let masks = &self.dictionary.masks;
+        let mut row_masks = [[0; N]; N];
+        for (row, row_mask) in self
+            .solution
+            .iter()
+            .zip(&mut row_masks)
+            .skip(first_open_row)
+        {
+            match masks.get(&to_slice(row)) {
+                Some(mask) => *row_mask = *mask,
+                None => {
+                    self.stats.pruned_by_prefix += 1;
+                    return false;
+                }
+            }
+        }
+        // Every letter some empty cell could still take
+        let mut open_letters = 0;
+        let open_cols = (first_open_col..N).map(|x| (x, self.solution.word_at_col(x)));
+        for (x, col) in open_cols {
+            let Some(col_mask) = masks.get(&to_slice(&col)) else {
+                self.stats.pruned_by_prefix += 1;
+                return false;
+            };
+            for y in (first_open_row..N).filter(|&y| col[y] == 0) {
+                let cell = row_masks[y][x] & col_mask[y] & self.allowed(y, x);
+                if cell == 0 {
+                    self.stats.pruned_by_mask += 1;
+                    return false;
+                }
+                open_letters |= cell;
+            }
+        }
+        if !self.fits_rules() {
+            self.stats.pruned_by_rules += 1;
+            return false;
+        }
+        if !self.fits_letters(open_letters) {
+            self.stats.pruned_by_letters += 1;
+            return false;
+        }
+        true
+    }
 
-    let letters = row_letters.intersection(&col_letters);
-    // println!("Found letters {:?}", letters.clone().collect_vec());
-    let mut solutions = Vec::new();
-    for letter in letters {
-        solution[4][4] = *letter;
-        solutions.push(solution.clone());
-        solutions.push(solution.transpose());
+    /// Whether the letters in the grid so far, and `open_letters` that its empty cells could
+    /// still take, can meet the letter rules
+    fn fits_letters(&self, open_letters: u32) -> bool {
+        if self.letters.is_empty() {
+            return true;
+        }
+        let placed = self.solution.iter().flatten().copied();
+        self.letters
+            .could_hold(placed.filter(|&letter| letter != 0), open_letters)
     }
-    solution[4][4] = 0;
 
-    solutions
+    /// Whether every required word could still go somewhere in the grid
+    fn fits_rules(&self) -> bool {
+        let solution = &self.solution;
+        self.rules
+            .could_hold::<N>(|y, x| Some(solution[y][x]).filter(|&letter| letter != 0))
+    }
 }
 
-fn are_cols_valid(cache: &HashMap<WordFrag<'_>, Vec<Word>>, solution: &Grid) -> bool {
-    for i in 0..5 {
+fn are_cols_valid<const N: usize>(
+    cache: &HashMap<WordFrag<'_>, Vec<Word<N>>>,
+    solution: &Grid<N>,
+) -> bool {
+    for i in 0..N {
         let col = col_index(solution, i);
         let col = to_slice(&col);
         if !cache.contains_key(&col) {
@@ -405,21 +1355,21 @@ fn are_cols_valid(cache: &HashMap<WordFrag<'_>, Vec<Word>>, solution: &Grid) ->
     true
 }
 
-fn col_index(solution: &[[u8; 5]; 5], index: usize) -> [u8; 5] {
-    let mut vals = [0; 5];
-    for y in 0..5 {
+fn col_index<const N: usize>(solution: &[[u8; N]; N], index: usize) -> [u8; N] {
+    let mut vals = [0; N];
+    for y in 0..N {
         vals[y] = solution[y][index];
     }
     vals
 }
 
 /// Gives the slice until the first zero
-fn to_slice(word: &[u8; 5]) -> WordFrag<'_> {
+fn to_slice<const N: usize>(word: &[u8; N]) -> WordFrag<'_> {
     let first_zero = word
         .iter()
         .find_position(|n| **n == 0)
         .map(|(i, _)| i)
-        .unwrap_or(5);
+        .unwrap_or(N);
     WordFrag(&word[0..first_zero])
 }
 
@@ -427,7 +1377,12 @@ fn to_slice(word: &[u8; 5]) -> WordFrag<'_> {
 mod tests {
     use std::hash::{DefaultHasher, Hash};
 
-    use crate::{BinSearchRange, SolutionFinder, finder::DoubleSidedFinder};
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use crate::{
+        BinSearchRange, SolutionFinder,
+        finder::{DoubleSidedFinder, TEST_WORDS},
+    };
 
     use super::*;
     extern crate test;
@@ -445,6 +1400,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cache_hit_longer_word() {
+        let words = vec![Word(*b"letters")];
+        let cache = starting_letters_cache(&words);
+        assert!(
+            cache.contains_key(&WordFrag::from(b"letters".as_slice())),
+            "Couldn't find {} in {:?}",
+            "letters",
+            cache,
+        );
+    }
+
     #[test]
     fn cache_hit_partial() {
         let words = vec![Word(*b"words")];
@@ -531,12 +1498,407 @@ mod tests {
             "grime", "honor", "outdo", "steed", "terse", "ghost", "route", "inter", "modes",
             "erode",
         ];
-        let words_ = convert(words.as_slice());
+        let words_: Vec<Word> = convert(words.as_slice());
         let cache = starting_letters_cache(&words_);
         let solutions = find_solutions(cache);
         assert_eq!(solutions.len(), 2);
     }
 
+    #[test]
+    fn splitting_by_first_row_matches_one_whole_search() {
+        let words_ = convert(&TEST_WORDS);
+        let cache = starting_letters_cache(&words_);
+        let dictionary = Dictionary::new(cache.clone());
+
+        let mut whole = Vec::new();
+        let mut collect = |grid: &Grid| whole.push(grid.clone());
+        let mut search = Search::new(&dictionary, Some(&mut collect));
+        search.place_pair_of_words(0);
+        assert_eq!(search.solution, Grid::default());
+        drop(search);
+
+        let split = find_solutions(cache);
+
+        assert!(!whole.is_empty());
+        assert_eq!(split, whole);
+    }
+
+    #[test]
+    fn masks_hold_the_letters_each_prefix_allows() {
+        let words: Vec<Word> = convert(&["words", "wards", "apple"]);
+        let dictionary = Dictionary::new(starting_letters_cache(&words));
+
+        let masks = dictionary.masks[&WordFrag(b"w")];
+
+        assert_eq!(masks[0], letter_bit(b'w'));
+        assert_eq!(masks[1], letter_bit(b'o') | letter_bit(b'a'));
+        assert_eq!(masks[4], letter_bit(b's'));
+    }
+
+    #[test]
+    fn forward_checking_keeps_every_solution() {
+        let words = TEST_WORDS;
+        let mut expected = DoubleSidedFinder::<BinSearchRange>::new(&words)
+            .find()
+            .into_iter()
+            .map(|sol| sol.to_string())
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        let found = std::sync::Mutex::new(vec![]);
+        let progress = std::sync::Mutex::new(vec![]);
+        let stats = for_each_solution(
+            &words,
+            &Constraints::<5>::default(),
+            &|sol| found.lock().unwrap().push(sol.join(",")),
+            &|update| progress.lock().unwrap().push(update),
+        );
+        let mut found = found.into_inner().unwrap();
+        found.sort();
+        let progress = progress.into_inner().unwrap();
+        let last = progress
+            .iter()
+            .max_by_key(|update| update.branches_done)
+            .unwrap();
+
+        assert_eq!(found, expected);
+        assert_eq!(stats.solutions, found.len() as u64);
+        assert_eq!(progress.len(), words.len());
+        assert_eq!(
+            (last.branches_done, last.branches),
+            (words.len(), words.len())
+        );
+        assert_eq!((last.solutions, last.nodes), (stats.solutions, stats.nodes));
+        assert!(stats.pruned_by_mask > 0);
+    }
+
+    #[test]
+    fn counting_matches_the_solutions_each_branch_finds() {
+        let words = TEST_WORDS;
+        let mut template: Template = Template::default();
+        template.fix(0, 1, ascii::AsciiChar::r);
+
+        for constraints in [
+            Constraints::default(),
+            Constraints {
+                template,
+                ..Default::default()
+            },
+        ] {
+            let found = Mutex::new(vec![]);
+            solutions_by_first_row(
+                &words,
+                &constraints,
+                &|_| false,
+                &|word, sols| {
+                    found
+                        .lock()
+                        .unwrap()
+                        .push((word.to_string(), sols.len() as u64))
+                },
+                &|_| {},
+            );
+            let expected = found
+                .into_inner()
+                .unwrap()
+                .into_iter()
+                .collect::<SolutionCounts>();
+
+            let (counts, stats) = count_by_first_row(&words, &constraints, &|_| {});
+
+            assert!(counts.total() > 0);
+            assert_eq!(counts, expected);
+            assert_eq!(stats.solutions, counts.total());
+        }
+    }
+
+    #[test]
+    fn shards_split_the_search_between_them() {
+        let words = TEST_WORDS;
+        let mut everything = solutions(&words);
+        everything.sort();
+
+        let mut sharded = (1..=3)
+            .flat_map(|number| {
+                let constraints = Constraints {
+                    shard: Shard { number, count: 3 },
+                    ..Default::default()
+                };
+                solutions_with(&words, &constraints)
+            })
+            .collect_vec();
+        sharded.sort();
+
+        assert_eq!(sharded, everything);
+    }
+
+    #[test]
+    fn canonical_searches_find_one_of_each_transposed_pair() {
+        let words = TEST_WORDS;
+        let mut fixed: Template = Template::default();
+        fixed.fix(0, 1, ascii::AsciiChar::r);
+
+        for template in [Template::default(), fixed] {
+            let constraints = Constraints {
+                template,
+                ..Default::default()
+            };
+            let everything = solutions_with(&words, &constraints)
+                .into_iter()
+                .map(crate::Solution::new)
+                .collect::<std::collections::BTreeSet<_>>();
+            let canonical = Constraints {
+                canonical: true,
+                ..constraints
+            };
+
+            let mut actual = solutions_with(&words, &canonical)
+                .into_iter()
+                .map(crate::Solution::new)
+                .collect_vec();
+            actual.sort();
+
+            let expected = everything
+                .iter()
+                .filter(|sol| sol.is_canonical() || !everything.contains(&sol.transpose()))
+                .cloned()
+                .collect_vec();
+            if template.is_blank() {
+                assert_eq!(actual.len() * 2, everything.len());
+            }
+            assert_eq!(actual, expected);
+            let (counts, _) = count_by_first_row(&words, &canonical, &|_| {});
+            assert_eq!(counts.total(), expected.len() as u64);
+        }
+    }
+
+    #[test]
+    fn word_rules_keep_only_the_solutions_meeting_them() {
+        let words = TEST_WORDS;
+        let everything = solutions(&words)
+            .into_iter()
+            .map(crate::Solution::new)
+            .collect_vec();
+        let rule_sets = [
+            vec!["outdo"],
+            vec!["honor@row2"],
+            vec!["honor@column2", "grime"],
+            vec!["!ghost"],
+            vec!["level"],
+        ];
+
+        for rule_set in rule_sets {
+            let mut rules = WordRules::default();
+            for rule in &rule_set {
+                match rule.strip_prefix('!') {
+                    Some(word) => rules.forbid(word),
+                    None => rules.require(rule.parse().unwrap()),
+                }
+            }
+            let mut expected = everything
+                .iter()
+                .filter(|sol| rules.matches(sol))
+                .cloned()
+                .collect_vec();
+            expected.sort();
+            let constraints = Constraints {
+                rules,
+                ..Default::default()
+            };
+
+            let mut actual = solutions_with(&words, &constraints)
+                .into_iter()
+                .map(crate::Solution::new)
+                .collect_vec();
+            actual.sort();
+
+            assert_eq!(actual, expected, "with {rule_set:?}");
+            if rule_set != ["level"] {
+                assert!(!actual.is_empty(), "with {rule_set:?}");
+            }
+            let (counts, stats) = count_by_first_row(&words, &constraints, &|_| {});
+            assert_eq!(counts.total(), expected.len() as u64);
+            if rule_set == ["level"] {
+                assert!(stats.pruned_by_rules > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn letter_rules_keep_only_the_solutions_meeting_them() {
+        let words = TEST_WORDS;
+        let everything = solutions(&words)
+            .into_iter()
+            .map(crate::Solution::new)
+            .collect_vec();
+        let mut banned = LetterRules::default();
+        banned.ban(ascii::AsciiChar::p);
+        let mut required = LetterRules::default();
+        required.require(ascii::AsciiChar::g);
+        let mut at_most = LetterRules::default();
+        at_most.limit_repeats(4);
+
+        for letters in [banned, required, at_most] {
+            let mut expected = everything
+                .iter()
+                .filter(|sol| letters.matches(sol))
+                .cloned()
+                .collect_vec();
+            expected.sort();
+            let constraints = Constraints {
+                letters,
+                ..Default::default()
+            };
+
+            let mut actual = solutions_with(&words, &constraints)
+                .into_iter()
+                .map(crate::Solution::new)
+                .collect_vec();
+            actual.sort();
+
+            assert_eq!(actual, expected, "with {letters:?}");
+            assert!(actual.len() < everything.len(), "with {letters:?}");
+            assert!(!actual.is_empty(), "with {letters:?}");
+            let (counts, stats) = count_by_first_row(&words, &constraints, &|_| {});
+            assert_eq!(counts.total(), expected.len() as u64);
+            if letters.max_repeats().is_some() {
+                assert!(stats.pruned_by_letters > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn a_node_budget_stops_the_search_part_way() {
+        let words = include_str!("../../words.txt")
+            .lines()
+            .take(2000)
+            .collect_vec();
+        let constraints: Constraints = Constraints::default();
+        let token = CancelToken::new().with_node_budget(10_000);
+        let found = Mutex::new(Vec::new());
+        let finished = Mutex::new(0);
+
+        let stats = for_each_solution_until(
+            &words,
+            &constraints,
+            &|sol| found.lock().unwrap().push(sol),
+            &|_| *finished.lock().unwrap() += 1,
+            &token,
+        );
+
+        assert!(stats.stopped);
+        assert!(token.is_cancelled());
+        assert!(*finished.lock().unwrap() < words.len());
+        let found = found.into_inner().unwrap();
+        assert_eq!(found.len() as u64, stats.solutions);
+    }
+
+    #[test]
+    fn timing_stops_part_way_through_a_branch_when_out_of_time() {
+        let words = include_str!("../../words.txt")
+            .lines()
+            .take(2000)
+            .collect_vec();
+        let mut rng = StdRng::seed_from_u64(5);
+
+        let (nodes, _) = time_branches::<5>(&words, Duration::ZERO, &mut rng);
+
+        assert!(nodes > 0);
+        assert!(nodes <= crate::cancel::CHECK_EVERY, "{nodes} nodes");
+    }
+
+    #[test]
+    fn templates_prune_to_the_matching_solutions() {
+        let words = TEST_WORDS;
+        let everything = solutions(&words);
+        let first = crate::Solution::new(everything[0]);
+
+        for squares in [vec![(0, 1)], vec![(1, 0), (4, 4)], vec![(2, 2)]] {
+            let mut template = Template::default();
+            for (y, x) in squares {
+                template.fix(y, x, first.rows[y].0[x]);
+            }
+            let mut expected = everything
+                .iter()
+                .filter(|sol| template.matches(&crate::Solution::new(**sol)))
+                .collect_vec();
+            expected.sort();
+
+            let mut actual = solutions_matching(&words, &template);
+            actual.sort();
+
+            assert!(!actual.is_empty(), "nothing matched {template}");
+            assert_eq!(actual.iter().collect_vec(), expected, "for {template}");
+        }
+    }
+
+    const THREE_LETTER_WORDS: [&str; 13] = [
+        "bat", "ape", "tea", "are", "rat", "eta", "tar", "art", "era", "ate", "eat", "tee", "ere",
+    ];
+
+    fn sorted_solutions(policy: DuplicatePolicy) -> Vec<[&'static str; 3]> {
+        let constraints = Constraints {
+            duplicates: policy,
+            ..Default::default()
+        };
+        let mut found = solutions_with(&THREE_LETTER_WORDS, &constraints);
+        found.sort();
+        found
+    }
+
+    fn columns_of(rows: &[&str; 3]) -> [String; 3] {
+        std::array::from_fn(|x| rows.iter().map(|row| row.as_bytes()[x] as char).collect())
+    }
+
+    #[test]
+    fn the_any_policy_finds_every_grid_of_words_once() {
+        let mut expected = (0..3)
+            .map(|_| THREE_LETTER_WORDS.iter().copied())
+            .multi_cartesian_product()
+            .map(|rows| <[&str; 3]>::try_from(rows).unwrap())
+            .filter(|rows| {
+                columns_of(rows)
+                    .iter()
+                    .all(|col| THREE_LETTER_WORDS.contains(&col.as_str()))
+            })
+            .collect_vec();
+        expected.sort();
+
+        assert_eq!(sorted_solutions(DuplicatePolicy::Any), expected);
+    }
+
+    #[test]
+    fn the_symmetric_policy_finds_classic_word_squares() {
+        let expected = sorted_solutions(DuplicatePolicy::Any)
+            .into_iter()
+            .filter(|rows| columns_of(rows) == rows.map(String::from))
+            .filter(|rows| rows.iter().all_unique())
+            .collect_vec();
+
+        let actual = sorted_solutions(DuplicatePolicy::Symmetric);
+
+        assert!(actual.contains(&["bat", "ape", "tea"]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn the_distinct_policy_never_repeats_a_word() {
+        let distinct = sorted_solutions(DuplicatePolicy::Distinct);
+        let any = sorted_solutions(DuplicatePolicy::Any);
+
+        for rows in &distinct {
+            assert!(any.contains(rows));
+            let columns = columns_of(rows);
+            assert!(
+                rows.iter()
+                    .copied()
+                    .chain(columns.iter().map(String::as_str))
+                    .all_unique()
+            );
+        }
+        assert!(distinct.len() < any.len());
+    }
+
     #[bench]
     #[ignore = "bench"]
     fn time_original(b: &mut Bencher) {
@@ -0,0 +1,207 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[cfg(feature = "multi-thread")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{RectangularSolution, letter_bit};
+
+/// Finds grids of `M` rows by `N` columns, where the rows come from one word list and the
+/// columns from another.
+///
+/// Every row has to be `N` letters long and every column `M` letters long, so words of the
+/// wrong length in either list are ignored.
+pub struct RectangleFinder<'a, const M: usize, const N: usize> {
+    /// Row words grouped by their first letter
+    rows: BTreeMap<u8, Vec<&'a [u8]>>,
+    /// For every prefix of a column word, the letters that can come next, as a mask
+    next_letters: HashMap<&'a [u8], u32>,
+}
+
+impl<'a, const M: usize, const N: usize> RectangleFinder<'a, M, N> {
+    pub fn new(rows: &'a [&'a str], columns: &'a [&'a str]) -> Self {
+        let mut grouped_rows = BTreeMap::<u8, Vec<&'a [u8]>>::new();
+        for row in rows.iter().map(|w| w.as_bytes()).filter(|w| w.len() == N) {
+            grouped_rows.entry(row[0]).or_default().push(row);
+        }
+
+        let mut next_letters = HashMap::<&'a [u8], u32>::new();
+        for column in columns
+            .iter()
+            .map(|w| w.as_bytes())
+            .filter(|w| w.len() == M)
+        {
+            for (i, letter) in column.iter().enumerate() {
+                *next_letters.entry(&column[0..i]).or_default() |= letter_bit(*letter);
+            }
+        }
+
+        Self {
+            rows: grouped_rows,
+            next_letters,
+        }
+    }
+
+    pub fn find(&self) -> Vec<RectangularSolution<M, N>> {
+        let sols = std::sync::Mutex::new(vec![]);
+        self.find_each(&|sol| sols.lock().unwrap().push(sol));
+        sols.into_inner().unwrap()
+    }
+
+    /// Hands each solution to `sink` as it's found, splitting the search by first row
+    pub fn find_each(&self, sink: &(dyn Fn(RectangularSolution<M, N>) + Sync)) {
+        let first_rows = self.rows.values().flatten().copied().collect::<Vec<_>>();
+        let search = |first_row: &&'a [u8]| {
+            let mut columns = [[0; M]; N];
+            let mut placed = Vec::with_capacity(M);
+            if self.place_row(first_row, &mut columns, &mut placed) {
+                self.fill_rows(&mut columns, &mut placed, &mut |sol| sink(sol));
+            }
+        };
+
+        #[cfg(feature = "multi-thread")]
+        first_rows.par_iter().for_each(search);
+
+        #[cfg(not(feature = "multi-thread"))]
+        first_rows.iter().for_each(search);
+    }
+
+    /// Tries each row word that every column prefix can still be extended by
+    fn fill_rows(
+        &self,
+        columns: &mut [[u8; M]; N],
+        placed: &mut Vec<&'a [u8]>,
+        sink: &mut dyn FnMut(RectangularSolution<M, N>),
+    ) {
+        let depth = placed.len();
+        if depth == M {
+            if let Some(sol) = self.solution(columns, placed) {
+                sink(sol);
+            }
+            return;
+        }
+
+        let Some(first_letters) = self.next_letters.get(&columns[0][0..depth]) else {
+            return;
+        };
+        let candidates = self
+            .rows
+            .iter()
+            .filter(|(letter, _)| first_letters & letter_bit(**letter) != 0)
+            .flat_map(|(_, rows)| rows.iter());
+        for row in candidates {
+            if self.place_row(row, columns, placed) {
+                self.fill_rows(columns, placed, sink);
+                placed.pop();
+            }
+        }
+    }
+
+    /// Writes `row` in below the rows already placed, if every column prefix it makes is the
+    /// start of a column word. Letters below the placed rows are stale and never read.
+    fn place_row(
+        &self,
+        row: &'a [u8],
+        columns: &mut [[u8; M]; N],
+        placed: &mut Vec<&'a [u8]>,
+    ) -> bool {
+        let depth = placed.len();
+        if placed.contains(&row) {
+            return false;
+        }
+        let fits = row.iter().zip(columns.iter()).all(|(letter, column)| {
+            self.next_letters
+                .get(&column[0..depth])
+                .is_some_and(|next| next & letter_bit(*letter) != 0)
+        });
+        if !fits {
+            return false;
+        }
+        for (letter, column) in row.iter().zip(columns.iter_mut()) {
+            column[depth] = *letter;
+        }
+        placed.push(row);
+        true
+    }
+
+    /// Every column of a full grid is a column word, so all that's left is checking no word is
+    /// used twice
+    fn solution(
+        &self,
+        columns: &[[u8; M]; N],
+        rows: &[&[u8]],
+    ) -> Option<RectangularSolution<M, N>> {
+        let words = rows
+            .iter()
+            .copied()
+            .chain(columns.iter().map(|column| column.as_slice()))
+            .collect::<HashSet<_>>();
+        if words.len() != M + N {
+            return None;
+        }
+        let rows = rows
+            .iter()
+            .map(|row| str::from_utf8(row).unwrap())
+            .collect::<Vec<_>>();
+        Some(RectangularSolution::new(
+            <[&str; M]>::try_from(rows).unwrap(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::finder::new_double_sided;
+
+    #[test]
+    fn rows_and_columns_come_from_their_own_lists() {
+        let rows = ["abc", "def", "ad", "be"];
+        let columns = ["ad", "be", "cf", "abc"];
+
+        let sols = RectangleFinder::<2, 3>::new(&rows, &columns).find();
+
+        assert_eq!(sols, vec![RectangularSolution::new(["abc", "def"])]);
+        assert_eq!(
+            sols[0].columns().map(String::from),
+            ["ad", "be", "cf"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn finds_five_by_six_rectangles() {
+        let rows = [
+            "abcde", "fghij", "klmno", "pqrst", "uvwxy", "zabcd", "abcdf",
+        ];
+        let columns = ["afkpuz", "bglqva", "chmrwb", "dinsxc", "ejotyd", "afkpuy"];
+
+        let sols = RectangleFinder::<6, 5>::new(&rows, &columns).find();
+
+        assert_eq!(
+            sols.iter().map(|sol| sol.to_string()).collect_vec(),
+            ["abcde,fghij,klmno,pqrst,uvwxy,zabcd"]
+        );
+    }
+
+    #[test]
+    fn square_rectangles_match_the_square_finder() {
+        let words = [
+            "abc", "def", "ghi", "adg", "beh", "cfi", "abd", "ceg", "hid",
+        ];
+
+        let mut expected = new_double_sided::solutions_of_size::<3>(&words)
+            .into_iter()
+            .map(|sol| sol.join(","))
+            .collect_vec();
+        let mut actual = RectangleFinder::<3, 3>::new(&words, &words)
+            .find()
+            .into_iter()
+            .map(|sol| sol.to_string())
+            .collect_vec();
+        expected.sort();
+        actual.sort();
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,189 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    BinSearchRange, HasSearchRange, LinearSearchRange, SolutionFinder,
+    finder::{
+        DoubleSidedFinder, MeetInTheMiddleFinder, MostConstrainedFinder, NewDoubleSidedFinder,
+        TopDownFinder, TrivialFinder,
+    },
+};
+
+/// Every search engine a solve can pick by name
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FinderKind {
+    Trivial,
+    TopDown,
+    #[default]
+    DoubleSidedBin,
+    DoubleSidedHash,
+    DoubleSidedLinear,
+    NewDoubleSided,
+    MostConstrained,
+    MeetInTheMiddle,
+}
+
+impl FinderKind {
+    pub const ALL: [Self; 8] = [
+        Self::Trivial,
+        Self::TopDown,
+        Self::DoubleSidedBin,
+        Self::DoubleSidedHash,
+        Self::DoubleSidedLinear,
+        Self::NewDoubleSided,
+        Self::MostConstrained,
+        Self::MeetInTheMiddle,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Trivial => "trivial",
+            Self::TopDown => "top-down",
+            Self::DoubleSidedBin => "double-sided-bin",
+            Self::DoubleSidedHash => "double-sided-hash",
+            Self::DoubleSidedLinear => "double-sided-linear",
+            Self::NewDoubleSided => "new-double-sided",
+            Self::MostConstrained => "most-constrained",
+            Self::MeetInTheMiddle => "meet-in-the-middle",
+        }
+    }
+
+    /// Whether this kind of finder can search `size` by `size` grids. The [`DoubleSidedFinder`]s
+    /// only handle 5 by 5 ones, and the rest handle any size.
+    pub fn supports_size(self, size: usize) -> bool {
+        size == 5
+            || !matches!(
+                self,
+                Self::DoubleSidedBin | Self::DoubleSidedHash | Self::DoubleSidedLinear
+            )
+    }
+
+    /// Builds this kind of finder over `words` with its default settings
+    pub fn create<'a>(self, words: &'a [&'a str]) -> Box<dyn SolutionFinder<'a> + Sync + 'a> {
+        match self {
+            Self::DoubleSidedBin => Box::new(DoubleSidedFinder::<BinSearchRange>::new(words)),
+            Self::DoubleSidedHash => Box::new(DoubleSidedFinder::<HasSearchRange>::new(words)),
+            Self::DoubleSidedLinear => Box::new(DoubleSidedFinder::<LinearSearchRange>::new(words)),
+            kind => kind
+                .create_sized(words)
+                .expect("every other finder searches any size of grid"),
+        }
+    }
+
+    /// Builds this kind of finder over `words` for `N` by `N` grids, or `None` for the
+    /// [`DoubleSidedFinder`]s, which only [`create`](Self::create) can build
+    pub fn create_sized<'a, const N: usize>(
+        self,
+        words: &'a [&'a str],
+    ) -> Option<Box<dyn SolutionFinder<'a, N> + Sync + 'a>> {
+        Some(match self {
+            Self::Trivial => Box::new(TrivialFinder::new(words)),
+            Self::TopDown => Box::new(TopDownFinder::new(words)),
+            Self::DoubleSidedBin | Self::DoubleSidedHash | Self::DoubleSidedLinear => return None,
+            Self::NewDoubleSided => Box::new(NewDoubleSidedFinder::new(words)),
+            Self::MostConstrained => Box::new(MostConstrainedFinder::new(words)),
+            Self::MeetInTheMiddle => Box::new(MeetInTheMiddleFinder::new(words)),
+        })
+    }
+}
+
+impl Display for FinderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FinderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(Self::name).join(", ");
+                format!("unknown finder {s:?}, expected one of {names}")
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cancel::CancelToken;
+
+    #[test]
+    fn names_round_trip() {
+        for kind in FinderKind::ALL {
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+        assert!("double-sided".parse::<FinderKind>().is_err());
+    }
+
+    #[test]
+    fn every_finder_finds_the_same_solutions() {
+        let words = vec![
+            "grime", "honor", "outdo", "steed", "terse", "ghost", "route", "inter", "modes",
+            "erode",
+        ];
+
+        for kind in FinderKind::ALL {
+            let mut actual = kind.create(&words).find();
+            actual.sort();
+
+            assert_eq!(
+                actual.iter().map(|sol| sol.to_string()).collect::<Vec<_>>(),
+                vec![
+                    "ghost,route,inter,modes,erode",
+                    "grime,honor,outdo,steed,terse"
+                ],
+                "{kind} found something else"
+            );
+        }
+    }
+
+    #[test]
+    fn every_finder_that_supports_the_size_finds_the_same_smaller_squares() {
+        let words = vec![
+            "abcd", "efgh", "ijkl", "mnop", "aeim", "bfjn", "cgko", "dhlp", "abce", "mnoq",
+        ];
+
+        for kind in FinderKind::ALL {
+            let finder = kind.create_sized::<4>(&words);
+
+            assert_eq!(finder.is_some(), kind.supports_size(4), "{kind}");
+            let Some(finder) = finder else {
+                continue;
+            };
+            let mut actual = finder.find();
+            actual.sort();
+            assert_eq!(
+                actual.iter().map(|sol| sol.to_string()).collect::<Vec<_>>(),
+                vec!["abcd,efgh,ijkl,mnop", "aeim,bfjn,cgko,dhlp"],
+                "{kind} found something else"
+            );
+        }
+        assert!(FinderKind::ALL.iter().all(|kind| kind.supports_size(5)));
+    }
+
+    #[test]
+    fn every_finder_stops_when_cancelled() {
+        let words = vec![
+            "grime", "honor", "outdo", "steed", "terse", "ghost", "route", "inter", "modes",
+            "erode",
+        ];
+        let cancelled = CancelToken::new();
+        cancelled.cancel();
+
+        for kind in FinderKind::ALL {
+            let finder = kind.create(&words);
+
+            let stopped = finder.find_until(&cancelled);
+            let finished = finder.find_until(&CancelToken::new());
+
+            assert!(!stopped.complete, "{kind} didn't notice it was cancelled");
+            assert!(stopped.found.is_empty(), "{kind} kept going");
+            assert!(finished.complete, "{kind} stopped without being cancelled");
+            assert_eq!(finished.found.len(), 2, "{kind} found something else");
+        }
+    }
+}
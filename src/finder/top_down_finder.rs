@@ -1,14 +1,19 @@
 use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 use crate::AddedWord;
-use crate::{Solution, SolutionFinder, WordList, builder::SolutionBuilder};
+use crate::{
+    Solution, SolutionFinder, WordList,
+    builder::SolutionBuilder,
+    cancel::{CancelToken, NodeMeter},
+};
 
-pub struct TopDownFinder<'a> {
+/// Finds every solution by filling the grid a row at a time from the top
+pub struct TopDownFinder<'a, const N: usize = 5> {
     word_list: WordList,
     words: &'a [&'a str],
 }
 
-impl<'a> SolutionFinder<'a> for TopDownFinder<'a> {
+impl<'a, const N: usize> SolutionFinder<'a, N> for TopDownFinder<'a, N> {
     fn new(words: &'a [&'a str]) -> Self {
         Self {
             word_list: WordList::new(words.to_vec()),
@@ -16,19 +21,33 @@ impl<'a> SolutionFinder<'a> for TopDownFinder<'a> {
         }
     }
 
-    fn find(&self) -> Vec<Solution> {
+    fn find(&self) -> Vec<Solution<N>> {
         find_solutions_new(&self.word_list, &self.words.to_vec())
     }
+
+    fn find_each_until(&self, sink: &(dyn Fn(Solution<N>) + Sync), token: &CancelToken) -> bool {
+        let possible_rows = self.words.to_vec();
+        possible_rows.par_iter().all(|word| {
+            let mut builder = SolutionBuilder::<N>::new(&self.word_list);
+            if builder.add(word).is_err() {
+                return true;
+            }
+            let mut meter = NodeMeter::new(token);
+            let mut sink = |sol| sink(sol);
+            subsolutions_until(&possible_rows, &mut builder, &mut sink, Some(&mut meter));
+            !meter.stopped()
+        })
+    }
 }
 
-pub fn find_solutions_new<'a>(
+pub fn find_solutions_new<'a, const N: usize>(
     possible_columns: &WordList,
     possible_rows: &'a Vec<&'a str>,
-) -> Vec<Solution> {
+) -> Vec<Solution<N>> {
     possible_rows
         .par_iter()
         .filter_map(|word| {
-            let mut builder = SolutionBuilder::new(possible_columns);
+            let mut builder = SolutionBuilder::<N>::new(possible_columns);
             builder.add(word).ok()?;
             let sols = find_subsolutions(possible_rows, &mut builder);
             Some(sols.into_par_iter())
@@ -37,24 +56,72 @@ pub fn find_solutions_new<'a>(
         .collect()
 }
 
-pub fn find_subsolutions<'a>(
+pub fn find_subsolutions<'a, const N: usize>(
     possible_rows: &'a Vec<&'a str>,
-    builder: &mut SolutionBuilder<'a>,
-) -> Vec<Solution> {
+    builder: &mut SolutionBuilder<'a, N>,
+) -> Vec<Solution<N>> {
     let mut solutions = vec![];
+    for_each_subsolution(possible_rows, builder, &mut |sol| solutions.push(sol));
+    solutions
+}
+
+pub fn for_each_subsolution<'a, const N: usize>(
+    possible_rows: &'a Vec<&'a str>,
+    builder: &mut SolutionBuilder<'a, N>,
+    sink: &mut dyn FnMut(Solution<N>),
+) {
+    subsolutions_until(possible_rows, builder, sink, None);
+}
+
+/// Like [`for_each_subsolution`], giving up once `meter` says to stop
+fn subsolutions_until<'a, const N: usize>(
+    possible_rows: &'a Vec<&'a str>,
+    builder: &mut SolutionBuilder<'a, N>,
+    sink: &mut dyn FnMut(Solution<N>),
+    mut meter: Option<&mut NodeMeter>,
+) {
     for word in possible_rows.iter() {
+        if meter.as_mut().is_some_and(|meter| meter.tick()) {
+            return;
+        }
         match builder.add(word) {
             Ok(AddedWord::Incomplete) => {
-                let mut sols = find_subsolutions(possible_rows, builder);
-                solutions.append(&mut sols);
+                subsolutions_until(possible_rows, builder, sink, meter.as_deref_mut());
                 builder.pop().unwrap();
             }
             Ok(AddedWord::Finished(sols)) => {
-                solutions.append(&mut Vec::from(*sols));
+                for sol in *sols {
+                    sink(sol);
+                }
                 builder.pop().unwrap();
             }
             Err(_) => {}
         };
     }
-    solutions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::finder::{TrivialFinder, sorted};
+
+    #[test]
+    fn finds_other_sized_squares() {
+        let three = [
+            "abc", "def", "ghi", "adg", "beh", "cfi", "abd", "ceg", "hid",
+        ];
+        let four = [
+            "abcd", "efgh", "ijkl", "mnop", "aeim", "bfjn", "cgko", "dhlp", "abce", "mnoq",
+        ];
+
+        assert_eq!(
+            sorted(TopDownFinder::<3>::new(&three).find()),
+            sorted(TrivialFinder::<3>::new(&three).find())
+        );
+        assert_eq!(
+            sorted(TopDownFinder::<4>::new(&four).find()),
+            sorted(TrivialFinder::<4>::new(&four).find())
+        );
+        assert!(!TopDownFinder::<4>::new(&four).find().is_empty());
+    }
 }
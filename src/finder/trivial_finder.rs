@@ -1,50 +1,60 @@
 use std::{collections::HashSet, ops::Deref};
 
-use crate::{Solution, SolutionFinder};
+use itertools::Itertools;
 
-pub struct TrivialFinder<'a> {
+use crate::{
+    Solution, SolutionFinder,
+    cancel::{CancelToken, NodeMeter},
+};
+
+pub struct TrivialFinder<'a, const N: usize = 5> {
     words: &'a [&'a str],
 }
 
-impl<'a> SolutionFinder<'a> for TrivialFinder<'a> {
+impl<'a, const N: usize> SolutionFinder<'a, N> for TrivialFinder<'a, N> {
     fn new(words: &'a [&'a str]) -> Self {
         Self { words }
     }
 
-    fn find(&self) -> Vec<crate::Solution> {
-        let mut sols = vec![];
-        (0..self.words.len()).for_each(|i| {
-            (0..self.words.len()).for_each(|j| {
-                (0..self.words.len()).for_each(|k| {
-                    (0..self.words.len()).for_each(|l| {
-                        (0..self.words.len()).for_each(|m| {
-                            let words = self.words;
-                            let possible_sol = [words[i], words[j], words[k], words[l], words[m]];
-                            if let Some(sol) = solution_validator(words, &possible_sol) {
-                                sols.push(sol);
-                            }
-                        });
-                    });
-                });
-            });
-        });
-        sols
+    fn find(&self) -> Vec<Solution<N>> {
+        let sols = std::sync::Mutex::new(vec![]);
+        self.find_each(&|sol| sols.lock().unwrap().push(sol));
+        sols.into_inner().unwrap()
+    }
+
+    fn find_each_until(&self, sink: &(dyn Fn(Solution<N>) + Sync), token: &CancelToken) -> bool {
+        let words = self.words;
+        let mut meter = NodeMeter::new(token);
+        for rows in (0..N).map(|_| 0..words.len()).multi_cartesian_product() {
+            if meter.tick() {
+                return false;
+            }
+            let possible_sol = rows.iter().map(|&i| words[i]).collect::<Vec<_>>();
+            if let Some(sol) = solution_validator(words, &possible_sol) {
+                sink(sol);
+            }
+        }
+        true
     }
 }
 
-fn solution_validator<'a>(words: &'a [&'a str], candidate: &'a [&'a str]) -> Option<Solution> {
-    if candidate.len() != 5 {
+fn solution_validator<'a, const N: usize>(
+    words: &'a [&'a str],
+    candidate: &'a [&'a str],
+) -> Option<Solution<N>> {
+    if candidate.len() != N {
         return None;
     }
-    if candidate.iter().any(|word| word.len() != 5) {
+    if candidate.iter().any(|word| word.len() != N) {
         return None;
     }
-    let columns = (0..5)
-        .map(|i| (0..5).map(|j| &candidate[j][i..i + 1]).collect())
+    let columns = (0..N)
+        .map(|i| (0..N).map(|j| &candidate[j][i..i + 1]).collect())
         .collect::<Vec<String>>();
 
     let iter = candidate
-        .iter().copied()
+        .iter()
+        .copied()
         .chain(columns.iter().map(|w| w.deref()));
 
     if iter.clone().any(|w| !words.contains(&w)) {
@@ -52,7 +62,7 @@ fn solution_validator<'a>(words: &'a [&'a str], candidate: &'a [&'a str]) -> Opt
     }
 
     let used_words: HashSet<&str> = HashSet::from_iter(iter.clone());
-    if used_words.len() == 10 {
+    if used_words.len() == 2 * N {
         Some(Solution::new(candidate.try_into().unwrap()))
     } else {
         None
@@ -1,9 +1,6 @@
 use std::collections::HashMap;
 
-use crate::{
-    Solution, Word,
-    finder::{Puzzle, PuzzleViewModel},
-};
+use crate::{Solution, Word, feedback::Feedback};
 
 pub fn entropy(distrobution: &[u32]) -> f64 {
     let size = distrobution.iter().sum::<u32>() as f64;
@@ -17,17 +14,13 @@ pub fn entropy(distrobution: &[u32]) -> f64 {
         .sum()
 }
 
+/// How many of `sols` show each different view after guessing `word`, bucketed by the
+/// [`Feedback`] it gives each of them
 pub fn distrobution_for(sols: &[Solution], word: Word) -> Vec<u32> {
-    let k = sols.iter().map(|s| {
-        let mut puzzle = Puzzle::new(s.clone());
-        puzzle.guess(word.clone());
-        puzzle.view()
-    });
-    let mut counts = HashMap::<PuzzleViewModel, u32>::new();
-    k.fold(&mut counts, |c, j| {
-        c.entry(j).and_modify(|a| *a += 1).or_insert(1);
-        c
-    });
+    let mut counts = HashMap::<Feedback, u32>::new();
+    for sol in sols {
+        *counts.entry(Feedback::new(sol, &word)).or_default() += 1;
+    }
     Vec::from_iter(counts.values().copied())
 }
 
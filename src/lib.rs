@@ -1,23 +1,44 @@
-#![feature(test)]
+#![cfg_attr(test, feature(test))]
 #![feature(iter_intersperse)]
 extern crate num_cpus;
 use ascii::{AsciiChar, AsciiStr, AsciiString};
 use builder::AddedWord;
-use finder::{Puzzle, PuzzleViewModel};
+use cancel::{CancelToken, Outcome};
+use commonness::{Commonness, Score};
+use finder::{Puzzle, PuzzleViewModel, SearchStats};
+use progress::Progress;
 use regex::Regex;
 use std::io;
 use std::str::FromStr;
-use std::{collections::HashMap, fmt::Display, fs::File, io::Read, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Display,
+    fs::File,
+    io::Read,
+    path::Path,
+};
+use template::Template;
 
 mod builder;
+pub mod cancel;
+pub mod checkpoint;
+pub mod cli;
+pub mod commonness;
+pub mod feedback;
 pub mod finder;
 pub mod first_guess;
+pub mod progress;
+pub mod rules;
+pub mod selfplay;
+pub mod shard;
 pub mod solver;
+pub mod template;
 
+/// A word of `N` letters, five unless the grid is a different size
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Word([AsciiChar; 5]);
+pub struct Word<const N: usize = 5>([AsciiChar; N]);
 
-impl From<&str> for Word {
+impl<const N: usize> From<&str> for Word<N> {
     fn from(value: &str) -> Self {
         let k = value
             .chars()
@@ -27,7 +48,7 @@ impl From<&str> for Word {
     }
 }
 
-impl From<String> for Word {
+impl<const N: usize> From<String> for Word<N> {
     fn from(value: String) -> Self {
         let k = value
             .chars()
@@ -37,33 +58,97 @@ impl From<String> for Word {
     }
 }
 
-impl From<AsciiString> for Word {
+impl<const N: usize> From<AsciiString> for Word<N> {
     fn from(value: AsciiString) -> Self {
         Self(value.chars().collect::<Vec<_>>().try_into().unwrap())
     }
 }
 
-impl From<&AsciiStr> for Word {
+impl<const N: usize> From<&AsciiStr> for Word<N> {
     fn from(value: &AsciiStr) -> Self {
         Self(value.chars().collect::<Vec<_>>().try_into().unwrap())
     }
 }
 
-impl From<Vec<AsciiChar>> for Word {
+impl<const N: usize> From<Vec<AsciiChar>> for Word<N> {
     fn from(value: Vec<AsciiChar>) -> Self {
         Self(value.try_into().unwrap())
     }
 }
 
-impl From<Word> for String {
-    fn from(value: Word) -> Self {
+impl<const N: usize> From<Word<N>> for String {
+    fn from(value: Word<N>) -> Self {
         String::from_iter(value.0.iter().map(|ch| ch.as_char()))
     }
 }
 
-pub trait SolutionFinder<'a> {
-    fn new(words: &'a [&'a str]) -> Self;
-    fn find(&self) -> Vec<Solution>;
+/// The bit for `letter` in the searches' letter masks. Letters that are equal mod 32 share a
+/// bit, which can only make a mask allow more.
+pub(crate) fn letter_bit(letter: u8) -> u32 {
+    1 << (letter % 32)
+}
+
+pub trait SolutionFinder<'a, const N: usize = 5> {
+    fn new(words: &'a [&'a str]) -> Self
+    where
+        Self: Sized;
+    fn find(&self) -> Vec<Solution<N>>;
+
+    /// Hands each solution to `sink` as it's found, giving up once `token` stops the search.
+    /// Returns whether the search got to the end.
+    fn find_each_until(&self, sink: &(dyn Fn(Solution<N>) + Sync), token: &CancelToken) -> bool;
+
+    /// Hands each solution to `sink` as it's found
+    fn find_each(&self, sink: &(dyn Fn(Solution<N>) + Sync)) {
+        self.find_each_until(sink, &CancelToken::new());
+    }
+
+    /// Like [`find`](Self::find), but returns what it found so far if `token` stops the search
+    fn find_until(&self, token: &CancelToken) -> Outcome<Vec<Solution<N>>> {
+        let found = std::sync::Mutex::new(Vec::new());
+        let complete = self.find_each_until(&|sol| found.lock().unwrap().push(sol), token);
+        Outcome {
+            found: found.into_inner().unwrap(),
+            complete,
+        }
+    }
+
+    /// Finds only the solutions with `template`'s letters, for finders that can prune with it
+    fn find_matching(&self, template: &Template<N>) -> Vec<Solution<N>> {
+        self.find()
+            .into_iter()
+            .filter(|sol| template.matches(sol))
+            .collect()
+    }
+}
+
+/// A finder that searches the branch under each first row on its own, so a search can be
+/// watched, counted one branch at a time and picked up again after being stopped
+pub trait FirstRowFinder<const N: usize = 5> {
+    /// Like [`SolutionFinder::find_each`], telling `observer` whenever a first row is done
+    fn find_each_observed(
+        &self,
+        sink: &(dyn Fn(Solution<N>) + Sync),
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> SearchStats;
+
+    /// Counts the solutions found under each first row without building any of them
+    fn count_by_first_row(
+        &self,
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> (SolutionCounts, SearchStats);
+
+    /// Searches one first row at a time so a caller can record progress between branches.
+    ///
+    /// First rows that `is_finished` accepts are skipped, and `on_finished` is handed every
+    /// solution found under a first row once that branch has been fully searched.
+    /// `observer` only counts the first rows that weren't already finished.
+    fn find_by_first_row(
+        &self,
+        is_finished: &(dyn Fn(&str) -> bool + Sync),
+        on_finished: &(dyn Fn(&str, Vec<Solution<N>>) + Sync),
+        observer: &(dyn Fn(Progress) + Sync),
+    ) -> SearchStats;
 }
 
 pub trait RangeFinder<'a> {
@@ -103,7 +188,7 @@ pub struct HasSearchRange(HashMap<AsciiString, std::ops::Range<usize>>);
 impl<'a> RangeFinder<'a> for HasSearchRange {
     fn init(words: &'a [Word]) -> Self {
         let mut map = HashMap::new();
-        for end in 1..5 {
+        for end in 1..=5 {
             for word in words.iter() {
                 let start = AsciiString::from(&word.0[0..end]);
                 let range = range_for_ascii(words, start.as_slice());
@@ -121,11 +206,11 @@ impl<'a> RangeFinder<'a> for HasSearchRange {
     }
 }
 
-pub struct LinearSearchRange<'a>(&'a [Word]);
+pub struct LinearSearchRange(Vec<Word>);
 
-impl<'a> RangeFinder<'a> for LinearSearchRange<'a> {
-    fn init(words: &'a [Word]) -> Self {
-        Self(words)
+impl<'a> RangeFinder<'a> for LinearSearchRange {
+    fn init(words: &[Word]) -> Self {
+        Self(Vec::from(words))
     }
 
     fn range(&self, new_word: &[AsciiChar]) -> std::ops::Range<usize> {
@@ -141,23 +226,35 @@ impl<'a> RangeFinder<'a> for LinearSearchRange<'a> {
             .iter()
             .position(|a| &a.0[0..new_word.len()] != new_word);
         let Some(end) = end else {
-            return start..start;
+            return start..self.0.len();
         };
         start..(start + end)
     }
 }
 
 pub fn get_words() -> Result<Vec<String>, io::Error> {
-    let path = Path::new("words.txt");
+    get_words_of_length(5)
+}
+
+pub fn get_words_of_length(length: usize) -> Result<Vec<String>, io::Error> {
+    read_words_of_length(Path::new("words.txt"), length)
+}
+
+/// The words in the word list at `path` with `length` letters, in the order they're listed
+pub fn read_words_of_length(path: &Path, length: usize) -> Result<Vec<String>, io::Error> {
     let mut file = File::open(path)?;
 
     let mut buffer = String::new();
     file.read_to_string(&mut buffer)?;
-    Ok(five_letter_words(&buffer))
+    Ok(words_of_length(&buffer, length))
 }
 
 pub fn five_letter_words(string: &str) -> Vec<String> {
-    let reg = Regex::new("^[a-z]{5}$").unwrap();
+    words_of_length(string, 5)
+}
+
+pub fn words_of_length(string: &str, length: usize) -> Vec<String> {
+    let reg = Regex::new(&format!("^[a-z]{{{length}}}$")).unwrap();
     let words = string.lines();
     words
         .filter(|word| -> bool { reg.is_match(word) })
@@ -165,36 +262,108 @@ pub fn five_letter_words(string: &str) -> Vec<String> {
         .collect()
 }
 
+/// Which grids count as solutions when it comes to the same word being used more than once
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DuplicatePolicy {
+    /// Every row and column is a different word
+    #[default]
+    Distinct,
+    /// Classic word squares, where each row is the same word as the matching column
+    Symmetric,
+    /// Any grid whose rows and columns are all words, whether or not some repeat
+    Any,
+}
+
+impl Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Distinct => "distinct",
+            Self::Symmetric => "symmetric",
+            Self::Any => "any",
+        })
+    }
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "distinct" => Ok(Self::Distinct),
+            "symmetric" => Ok(Self::Symmetric),
+            "any" => Ok(Self::Any),
+            _ => Err(format!(
+                "unknown duplicate policy {s:?}, expected distinct, symmetric or any"
+            )),
+        }
+    }
+}
+
+/// A filled `N`×`N` grid, given by its rows
 #[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord)]
-pub struct Solution {
-    pub rows: [Word; 5],
+pub struct Solution<const N: usize = 5> {
+    pub rows: [Word<N>; N],
 }
 
-impl Solution {
-    pub fn new<S: Into<Word>>(rows: [S; 5]) -> Self {
+impl<const N: usize> Solution<N> {
+    pub fn new<S: Into<Word<N>>>(rows: [S; N]) -> Self {
         Self {
             rows: rows.map(|s| s.into()),
         }
     }
 
-    pub fn does_match(&self, view: &PuzzleViewModel) -> bool {
-        let my_view = {
-            let mut puzzle = Puzzle::new(self.clone());
-            view.guesses.iter().for_each(|guess| {
-                puzzle.guess(guess.clone());
-            });
-            puzzle.view()
-        };
+    /// The words reading down each column, from left to right
+    pub fn columns(&self) -> [Word<N>; N] {
+        std::array::from_fn(|x| Word(std::array::from_fn(|y| self.rows[y].0[x])))
+    }
+
+    /// The same grid flipped about its diagonal, which is also a solution
+    pub fn transpose(&self) -> Self {
+        Self {
+            rows: self.columns(),
+        }
+    }
 
-        my_view == *view
+    /// Whichever of this solution and its transpose sorts first, so both have the same one
+    pub fn canonical(&self) -> Self {
+        self.clone().min(self.transpose())
     }
 
-    pub fn is_equivalent_to(&self, _other: &PuzzleViewModel) -> bool {
-        todo!()
+    pub fn is_canonical(&self) -> bool {
+        *self <= self.transpose()
+    }
+
+    /// How common this solution's rows and columns are
+    pub fn score(&self, commonness: &Commonness) -> Score {
+        let scores = self
+            .rows
+            .iter()
+            .chain(self.columns().iter())
+            .map(|word| commonness.score(&String::from(word.clone())))
+            .collect::<Vec<_>>();
+        Score {
+            min: scores.iter().copied().fold(f64::INFINITY, f64::min),
+            mean: scores.iter().sum::<f64>() / scores.len() as f64,
+        }
+    }
+
+    /// Whether this solution would have given the same feedback as `view` for its guesses
+    pub fn does_match(&self, view: &PuzzleViewModel<N>) -> bool {
+        self.is_equivalent_to(view)
+    }
+
+    /// Whether playing `other`'s guesses against this solution gives a view equivalent to it,
+    /// in the sense of [`PuzzleViewModel::is_equivalent_to`]
+    pub fn is_equivalent_to(&self, other: &PuzzleViewModel<N>) -> bool {
+        let mut puzzle = Puzzle::new(self.clone());
+        other.guesses.iter().for_each(|guess| {
+            puzzle.guess(guess.clone());
+        });
+        puzzle.view().is_equivalent_to(other)
     }
 }
 
-impl Display for Solution {
+impl<const N: usize> Display for Solution<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(
             &self
@@ -207,7 +376,7 @@ impl Display for Solution {
     }
 }
 
-impl FromStr for Solution {
+impl<const N: usize> FromStr for Solution<N> {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -215,7 +384,7 @@ impl FromStr for Solution {
             .split(',')
             .filter_map(|w| AsciiString::from_ascii(w).ok())
             .collect::<Vec<AsciiString>>();
-        if words.len() == 5 {
+        if words.len() == N && words.iter().all(|w| w.len() == N) {
             Ok(Self {
                 rows: words
                     .iter()
@@ -230,6 +399,131 @@ impl FromStr for Solution {
     }
 }
 
+/// Which solutions to keep when reading a list that may only have one of each transposed pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolutionSet {
+    /// Every solution and its transpose
+    Expanded,
+    /// Only the canonical form of each solution
+    Canonical,
+}
+
+/// Reads one solution per line, as in `solutions.txt`, sorted and without duplicates. Either set
+/// can be read from a file with either, since each is made from the other.
+pub fn read_solutions<const N: usize>(
+    text: &str,
+    set: SolutionSet,
+) -> Result<Vec<Solution<N>>, String> {
+    let mut solutions = BTreeSet::new();
+    for (number, line) in text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+    {
+        let sol = line
+            .parse::<Solution<N>>()
+            .map_err(|_| format!("line {}: invalid solution {line:?}", number + 1))?;
+        match set {
+            SolutionSet::Expanded => {
+                solutions.insert(sol.transpose());
+                solutions.insert(sol);
+            }
+            SolutionSet::Canonical => {
+                solutions.insert(sol.canonical());
+            }
+        }
+    }
+    Ok(solutions.into_iter().collect())
+}
+
+/// A filled grid of `M` rows that are each `N` letters long, so its `N` columns are `M` long
+#[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord, Hash)]
+pub struct RectangularSolution<const M: usize, const N: usize> {
+    pub rows: [Word<N>; M],
+}
+
+impl<const M: usize, const N: usize> RectangularSolution<M, N> {
+    pub fn new<S: Into<Word<N>>>(rows: [S; M]) -> Self {
+        Self {
+            rows: rows.map(|s| s.into()),
+        }
+    }
+
+    pub fn columns(&self) -> [Word<M>; N] {
+        std::array::from_fn(|x| Word(std::array::from_fn(|y| self.rows[y].0[x])))
+    }
+}
+
+impl<const M: usize, const N: usize> Display for RectangularSolution<M, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(
+            &self
+                .rows
+                .iter()
+                .map(|s| String::from(s.clone()))
+                .intersperse(String::from(","))
+                .collect::<String>(),
+        )
+    }
+}
+
+impl<const M: usize, const N: usize> FromStr for RectangularSolution<M, N> {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s
+            .split(',')
+            .filter_map(|w| AsciiString::from_ascii(w).ok())
+            .collect::<Vec<AsciiString>>();
+        if words.len() == M && words.iter().all(|w| w.len() == N) {
+            Ok(Self {
+                rows: words
+                    .iter()
+                    .map(|s| Word::from(s.clone()))
+                    .collect::<Vec<_>>()
+                    .try_into()
+                    .unwrap(),
+            })
+        } else {
+            Err(())
+        }
+    }
+}
+
+/// How many solutions a search found, split up by the first row word of the search branch
+/// that found them. A branch also counts the transposes it hands on, so those are filed under
+/// the branch rather than under their own first row.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SolutionCounts {
+    /// Only branches that found something are listed
+    pub by_first_row: BTreeMap<String, u64>,
+}
+
+impl SolutionCounts {
+    pub fn total(&self) -> u64 {
+        self.by_first_row.values().sum()
+    }
+}
+
+impl FromIterator<(String, u64)> for SolutionCounts {
+    fn from_iter<T: IntoIterator<Item = (String, u64)>>(iter: T) -> Self {
+        let mut counts = Self::default();
+        for (word, count) in iter.into_iter().filter(|(_, count)| *count > 0) {
+            *counts.by_first_row.entry(word).or_default() += count;
+        }
+        counts
+    }
+}
+
+impl Display for SolutionCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (word, count) in &self.by_first_row {
+            writeln!(f, "{word} {count}")?;
+        }
+        writeln!(f, "total {}", self.total())
+    }
+}
+
 pub struct WordList {
     words: HashMap<char, Box<WordList>>,
 }
@@ -275,7 +569,10 @@ impl WordList {
 mod my_test {
     use crate::{
         builder::SolutionBuilder,
-        finder::{DoubleSidedFinder, Puzzle, TopDownFinder, TrivialFinder, find_subsolutions},
+        finder::{
+            DoubleSidedFinder, LetterPlayed, Puzzle, TopDownFinder, TrivialFinder,
+            find_subsolutions,
+        },
     };
     use pretty_assertions::assert_eq;
 
@@ -339,7 +636,43 @@ mod my_test {
         ];
         let list = WordList::new(valid_words.clone());
 
-        b.iter(|| find_subsolutions(&valid_words, &mut SolutionBuilder::new(&list)))
+        b.iter(|| find_subsolutions(&valid_words, &mut SolutionBuilder::<5>::new(&list)))
+    }
+
+    #[test]
+    fn a_transpose_swaps_rows_and_columns() {
+        let sol = Solution::new(["grime", "honor", "outdo", "steed", "terse"]);
+        let flipped = Solution::new(["ghost", "route", "inter", "modes", "erode"]);
+
+        assert_eq!(sol.columns(), flipped.rows);
+        assert_eq!(sol.transpose(), flipped);
+        assert_eq!(sol.transpose().transpose(), sol);
+    }
+
+    #[test]
+    fn a_solution_and_its_transpose_share_a_canonical_form() {
+        let sol = Solution::new(["grime", "honor", "outdo", "steed", "terse"]);
+        let flipped = sol.transpose();
+
+        assert_eq!(sol.canonical(), flipped);
+        assert_eq!(flipped.canonical(), flipped);
+        assert!(flipped.is_canonical());
+        assert!(!sol.is_canonical());
+    }
+
+    #[test]
+    fn reads_either_set_from_either_file() {
+        let expanded = "grime,honor,outdo,steed,terse\nghost,route,inter,modes,erode\n";
+        let canonical = "ghost,route,inter,modes,erode\n";
+
+        for text in [expanded, canonical] {
+            let all = read_solutions::<5>(text, SolutionSet::Expanded).unwrap();
+            let one = read_solutions::<5>(text, SolutionSet::Canonical).unwrap();
+
+            assert_eq!(all.len(), 2);
+            assert_eq!(one, vec![all[0].clone()]);
+        }
+        assert!(read_solutions::<5>("ghost", SolutionSet::Expanded).is_err());
     }
 
     #[test]
@@ -365,6 +698,61 @@ mod my_test {
         assert_eq!(actual, false);
     }
 
+    #[test]
+    fn matches_views_whatever_order_their_hints_are_in() {
+        let answer = Solution::new(["grime", "honor", "outdo", "steed", "terse"]);
+        let mut puzzle = Puzzle::new(answer.clone());
+        puzzle.guess("arose".into());
+        let view = puzzle.view();
+        let mut shuffled = view.clone();
+        shuffled.hints[1] = "or".into();
+        let mut wrong_alphabet = view.clone();
+        wrong_alphabet
+            .alphabet
+            .insert(AsciiChar::a, LetterPlayed::AllUsed);
+
+        assert_ne!(shuffled, view);
+        assert!(answer.is_equivalent_to(&shuffled));
+        assert!(answer.does_match(&shuffled));
+        assert!(!answer.is_equivalent_to(&wrong_alphabet));
+    }
+
+    #[test]
+    fn four_by_four_puzzles_reveal_guessed_letters() {
+        let answer = Solution::<4>::new(["abcd", "efgh", "ijkl", "mnop"]);
+        let mut puzzle = Puzzle::new(answer.clone());
+        puzzle.guess("afxx".into());
+        let view = puzzle.view();
+
+        assert_eq!(view.grid[0][0], Some(AsciiChar::a));
+        assert_eq!(view.grid[1][1], Some(AsciiChar::f));
+        assert_eq!(view.grid[2][2], None);
+        assert!(answer.does_match(&view));
+    }
+
+    #[test]
+    fn reads_words_of_any_length() {
+        let words = words_of_length("cat\nhorse\nbird\nemu\nBAT", 3);
+
+        assert_eq!(words, vec!["cat", "emu"]);
+        assert_eq!(
+            "abc,def,ghi".parse::<Solution<3>>().unwrap().to_string(),
+            "abc,def,ghi"
+        );
+        assert!("abcd,efgh,ijkl".parse::<Solution<3>>().is_err());
+    }
+
+    #[test]
+    fn solution_counts_only_list_branches_that_found_something() {
+        let counts = [("ghost", 2), ("grime", 0), ("ghost", 1), ("route", 4)]
+            .map(|(word, count)| (word.to_string(), count))
+            .into_iter()
+            .collect::<SolutionCounts>();
+
+        assert_eq!(counts.total(), 7);
+        assert_eq!(counts.to_string(), "ghost 3\nroute 4\ntotal 7\n");
+    }
+
     #[test]
     fn range_a_b_c_for_b() {
         let list = ["a", "b", "c"];
@@ -499,7 +887,7 @@ mod my_test {
             sol
         };
         let known = {
-            let k = TrivialFinder::new(&words);
+            let k = TrivialFinder::<5>::new(&words);
             let mut sol = k.find();
             sol.sort();
             sol
@@ -523,7 +911,7 @@ mod my_test {
             sol
         };
         let known = {
-            let k = TrivialFinder::new(&words);
+            let k = TrivialFinder::<5>::new(&words);
             let mut sol = k.find();
             sol.sort();
             sol
@@ -577,6 +965,43 @@ mod my_test {
         range_simple::<BinSearchRange>(&source);
     }
 
+    #[test]
+    fn range_simple_hash() {
+        let source = simple_input();
+        range_simple::<HasSearchRange>(&source);
+    }
+
+    #[test]
+    fn range_linear_runs_to_the_end_of_the_list() {
+        let source = simple_input();
+        let finder = LinearSearchRange::init(&source);
+
+        assert_eq!(finder.range(&[AsciiChar::c]), 4..6);
+    }
+
+    #[test]
+    fn range_hash_finds_whole_words() {
+        let source = simple_input();
+        let finder = HasSearchRange::init(&source);
+
+        assert_eq!(finder.range(&source[3].0), 3..4);
+    }
+
+    #[test]
+    fn double_sided_finds_the_same_with_every_range_finder() {
+        let words = crate::finder::TEST_WORDS;
+        let mut binary = DoubleSidedFinder::<BinSearchRange>::new(&words).find();
+        binary.sort();
+        let mut hash = DoubleSidedFinder::<HasSearchRange>::new(&words).find();
+        hash.sort();
+        let mut linear = DoubleSidedFinder::<LinearSearchRange>::new(&words).find();
+        linear.sort();
+
+        assert!(!binary.is_empty());
+        assert_eq!(hash, binary);
+        assert_eq!(linear, binary);
+    }
+
     const WORDS: &str = include_str!("../all_words.txt");
 
     #[bench]
@@ -1,6 +1,6 @@
 use crate::{
-    finder::{DoubleSidedFinder, TopDownFinder},
     BinSearchRange, SolutionFinder,
+    finder::{DoubleSidedFinder, TopDownFinder},
 };
 
 use proptest::{prelude::*, sample::subsequence};
@@ -12,7 +12,7 @@ proptest! {
     #[test]
     fn s(k in my_words()) {
         let a = DoubleSidedFinder::<BinSearchRange>::new(&k);
-        let b = TopDownFinder::new(&k);
+        let b = TopDownFinder::<5>::new(&k);
 
         let a = a.find();
         let b = b.find();
@@ -0,0 +1,209 @@
+use std::{
+    fmt::Display,
+    io::{IsTerminal, Write},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// How far a search has got, handed to an observer each time one of its top-level branches
+/// finishes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    pub branches_done: usize,
+    /// Branches this run has to search, leaving out any a resumed run had already finished
+    pub branches: usize,
+    pub solutions: u64,
+    /// Rows and columns placed so far, counted once their branch is done
+    pub nodes: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    pub fn nodes_per_second(&self) -> f64 {
+        self.nodes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Guesses that the branches left take as long on average as the ones already done
+    pub fn time_left(&self) -> Option<Duration> {
+        if self.branches_done == 0 {
+            return None;
+        }
+        let remaining = self.branches - self.branches_done;
+        Some(
+            self.elapsed
+                .mul_f64(remaining as f64 / self.branches_done as f64),
+        )
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percent = 100.0 * self.branches_done as f64 / self.branches.max(1) as f64;
+        write!(
+            f,
+            "{}/{} branches ({percent:.1}%), {} solutions, {:.0} nodes/s",
+            self.branches_done,
+            self.branches,
+            self.solutions,
+            self.nodes_per_second()
+        )?;
+        match self.time_left() {
+            Some(left) => write!(f, ", about {} left", format_duration(left)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The two largest units of `duration`, like `2d 4h` or `3m 12s`
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let units = [
+        (secs / 86_400, "d"),
+        (secs / 3_600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+    let first = units
+        .iter()
+        .position(|(amount, _)| *amount > 0)
+        .unwrap_or(units.len() - 1);
+    units[first..]
+        .iter()
+        .take(2)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Adds up the work of top-level branches, which may finish on different threads
+#[derive(Debug)]
+pub struct ProgressTracker {
+    branches: usize,
+    done: AtomicUsize,
+    solutions: AtomicU64,
+    nodes: AtomicU64,
+    started: Instant,
+}
+
+impl ProgressTracker {
+    pub fn new(branches: usize) -> Self {
+        Self {
+            branches,
+            done: AtomicUsize::new(0),
+            solutions: AtomicU64::new(0),
+            nodes: AtomicU64::new(0),
+            started: Instant::now(),
+        }
+    }
+
+    /// Records one more finished branch and returns the progress including it
+    pub fn finish_branch(&self, solutions: u64, nodes: u64) -> Progress {
+        Progress {
+            branches_done: self.done.fetch_add(1, Ordering::Relaxed) + 1,
+            branches: self.branches,
+            solutions: self.solutions.fetch_add(solutions, Ordering::Relaxed) + solutions,
+            nodes: self.nodes.fetch_add(nodes, Ordering::Relaxed) + nodes,
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
+
+/// Shows progress on stderr at most once every `interval`, and always for the last branch.
+///
+/// On a terminal each update overwrites the last; otherwise, such as when stderr goes to a log
+/// file, every update gets a line of its own.
+#[derive(Debug)]
+pub struct StatusLine {
+    interval: Duration,
+    last_shown: Mutex<Option<Instant>>,
+}
+
+impl StatusLine {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_shown: Mutex::new(None),
+        }
+    }
+
+    pub fn update(&self, progress: Progress) {
+        let finished = progress.branches_done == progress.branches;
+        {
+            let mut last_shown = self.last_shown.lock().unwrap();
+            if !finished && last_shown.is_some_and(|last| last.elapsed() < self.interval) {
+                return;
+            }
+            *last_shown = Some(Instant::now());
+        }
+
+        let mut stderr = std::io::stderr().lock();
+        let _ = if !stderr.is_terminal() {
+            writeln!(stderr, "{progress}")
+        } else if finished {
+            writeln!(stderr, "\r\x1b[2K{progress}")
+        } else {
+            write!(stderr, "\r\x1b[2K{progress}")
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn progress(branches_done: usize, elapsed: Duration) -> Progress {
+        Progress {
+            branches_done,
+            branches: 100,
+            solutions: 12,
+            nodes: 3_000,
+            elapsed,
+        }
+    }
+
+    #[test]
+    fn the_time_left_assumes_branches_keep_the_same_pace() {
+        let progress = progress(25, Duration::from_secs(60));
+
+        assert_eq!(progress.time_left(), Some(Duration::from_secs(180)));
+        assert_eq!(progress.nodes_per_second(), 50.0);
+    }
+
+    #[test]
+    fn nothing_is_guessed_before_a_branch_finishes() {
+        let progress = progress(0, Duration::from_secs(60));
+
+        assert_eq!(progress.time_left(), None);
+        assert_eq!(
+            progress.to_string(),
+            "0/100 branches (0.0%), 12 solutions, 50 nodes/s"
+        );
+    }
+
+    #[test]
+    fn shows_the_two_largest_units_of_time() {
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86_400 + 4 * 3_600 + 5)),
+            "2d 4h"
+        );
+        assert_eq!(format_duration(Duration::from_secs(3 * 60 + 12)), "3m 12s");
+        assert_eq!(format_duration(Duration::from_secs(7)), "7s");
+        assert_eq!(format_duration(Duration::ZERO), "0s");
+    }
+
+    #[test]
+    fn the_tracker_adds_up_every_branch() {
+        let tracker = ProgressTracker::new(3);
+
+        tracker.finish_branch(2, 100);
+        let progress = tracker.finish_branch(0, 50);
+
+        assert_eq!(progress.branches_done, 2);
+        assert_eq!(progress.branches, 3);
+        assert_eq!(progress.solutions, 2);
+        assert_eq!(progress.nodes, 150);
+    }
+}
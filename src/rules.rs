@@ -0,0 +1,389 @@
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+use ascii::AsciiChar;
+
+use crate::{Solution, letter_bit};
+
+/// Where in the grid a required word has to go, counting rows and columns from 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slot {
+    Any,
+    Row(usize),
+    Column(usize),
+}
+
+impl Slot {
+    pub fn transpose(self) -> Self {
+        match self {
+            Self::Any => Self::Any,
+            Self::Row(y) => Self::Column(y),
+            Self::Column(x) => Self::Row(x),
+        }
+    }
+}
+
+/// A word every solution has to have, written as `theme` for any row or column, or
+/// `theme@row2` and `theme@column3` for a particular one counting from 1
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequiredWord {
+    pub word: String,
+    pub slot: Slot,
+}
+
+impl Display for RequiredWord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.slot {
+            Slot::Any => write!(f, "{}", self.word),
+            Slot::Row(y) => write!(f, "{}@row{}", self.word, y + 1),
+            Slot::Column(x) => write!(f, "{}@column{}", self.word, x + 1),
+        }
+    }
+}
+
+impl FromStr for RequiredWord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (word, slot) = s.split_once('@').unwrap_or((s, ""));
+        if word.is_empty() || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+            return Err(format!(
+                "required words have to be lowercase letters, got {word:?}"
+            ));
+        }
+        let index = |number: &str| match number.parse::<usize>() {
+            Ok(number) if number > 0 => Ok(number - 1),
+            _ => Err(format!(
+                "expected a row or column counting from 1, got {number:?}"
+            )),
+        };
+        let slot = if slot.is_empty() {
+            Slot::Any
+        } else if let Some(number) = slot.strip_prefix("row") {
+            Slot::Row(index(number)?)
+        } else if let Some(number) = slot.strip_prefix("column") {
+            Slot::Column(index(number)?)
+        } else {
+            return Err(format!(
+                "expected @row<n> or @column<n> after the word, got {slot:?}"
+            ));
+        };
+        Ok(Self {
+            word: word.to_string(),
+            slot,
+        })
+    }
+}
+
+/// Words a solution has to have, and words it mustn't have anywhere
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct WordRules {
+    required: Vec<RequiredWord>,
+    forbidden: BTreeSet<String>,
+}
+
+impl WordRules {
+    /// Rules that allow every solution
+    pub const NONE: Self = Self {
+        required: Vec::new(),
+        forbidden: BTreeSet::new(),
+    };
+
+    pub fn require(&mut self, word: RequiredWord) {
+        self.required.push(word);
+    }
+
+    pub fn forbid(&mut self, word: impl Into<String>) {
+        self.forbidden.insert(word.into());
+    }
+
+    pub fn required(&self) -> &[RequiredWord] {
+        &self.required
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.required.is_empty() && self.forbidden.is_empty()
+    }
+
+    /// Makes sure every required word could go in a `size` by `size` grid, since one that
+    /// can't would rule out every solution without saying why
+    pub fn check(&self, size: usize) -> Result<(), String> {
+        for required in &self.required {
+            if required.word.len() != size {
+                return Err(format!(
+                    "required word {:?} has to be {size} letters long",
+                    required.word
+                ));
+            }
+            let index = match required.slot {
+                Slot::Any => 0,
+                Slot::Row(i) | Slot::Column(i) => i,
+            };
+            if index >= size {
+                return Err(format!(
+                    "{required} is outside the grid, which only has {size} rows and columns"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `word` may go in a solution at all
+    pub fn allows(&self, word: &str) -> bool {
+        !self.forbidden.contains(word)
+    }
+
+    /// Whether the rules treat a grid and its transpose the same way
+    pub fn is_symmetric(&self) -> bool {
+        self.required
+            .iter()
+            .all(|required| required.slot == Slot::Any)
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self {
+            required: self
+                .required
+                .iter()
+                .map(|required| RequiredWord {
+                    slot: required.slot.transpose(),
+                    ..required.clone()
+                })
+                .collect(),
+            forbidden: self.forbidden.clone(),
+        }
+    }
+
+    /// Whether a partly filled grid could still end up with every required word, given the
+    /// `letter` in each square or `None` where it's still empty
+    pub fn could_hold<const N: usize>(&self, letter: impl Fn(usize, usize) -> Option<u8>) -> bool {
+        self.required.iter().all(|required| {
+            let word = required.word.as_bytes();
+            let fits = |at: &dyn Fn(usize) -> Option<u8>| {
+                (0..N).all(|i| at(i).is_none_or(|letter| letter == word[i]))
+            };
+            let fits_row = |y: usize| y < N && fits(&|x| letter(y, x));
+            let fits_column = |x: usize| x < N && fits(&|y| letter(y, x));
+            word.len() == N
+                && match required.slot {
+                    Slot::Any => (0..N).any(fits_row) || (0..N).any(fits_column),
+                    Slot::Row(y) => fits_row(y),
+                    Slot::Column(x) => fits_column(x),
+                }
+        })
+    }
+
+    pub fn matches<const N: usize>(&self, solution: &Solution<N>) -> bool {
+        let columns = solution.columns();
+        self.could_hold::<N>(|y, x| Some(solution.rows[y].0[x].as_byte()))
+            && solution
+                .rows
+                .iter()
+                .chain(columns.iter())
+                .all(|word| self.allows(&String::from(word.clone())))
+    }
+}
+
+/// Written as the options that give these rules, like `--require grime@row1 --forbid level`
+impl Display for WordRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let required = self.required.iter().map(|word| format!("--require {word}"));
+        let forbidden = self.forbidden.iter().map(|word| format!("--forbid {word}"));
+        f.write_str(&required.chain(forbidden).collect::<Vec<_>>().join(" "))
+    }
+}
+
+/// Rules about the letters in a grid's squares rather than the words they make
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LetterRules {
+    /// The most squares any one letter can fill
+    max_repeats: Option<usize>,
+    banned: u32,
+    required: u32,
+}
+
+impl LetterRules {
+    /// Lets no letter fill more than `max` squares
+    pub fn limit_repeats(&mut self, max: usize) {
+        self.max_repeats = Some(max);
+    }
+
+    pub fn max_repeats(&self) -> Option<usize> {
+        self.max_repeats
+    }
+    pub fn ban(&mut self, letter: AsciiChar) {
+        self.banned |= letter_bit(letter.as_byte());
+    }
+
+    pub fn require(&mut self, letter: AsciiChar) {
+        self.required |= letter_bit(letter.as_byte());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_repeats.is_none() && self.banned == 0 && self.required == 0
+    }
+
+    /// Whether `word` can go in a grid at all, which it can't with a banned letter in it
+    pub fn allows(&self, word: &str) -> bool {
+        word.bytes()
+            .all(|letter| letter_bit(letter) & self.banned == 0)
+    }
+
+    /// Whether a partly filled grid could still meet the rules, given the letters `placed` in it
+    /// so far and a mask of the letters its empty squares could still take
+    pub fn could_hold(&self, placed: impl IntoIterator<Item = u8>, open: u32) -> bool {
+        let mut counts = [0; 32];
+        let mut present = 0;
+        for letter in placed {
+            counts[(letter % 32) as usize] += 1;
+            present |= letter_bit(letter);
+        }
+        let too_many = self
+            .max_repeats
+            .is_some_and(|max| counts.iter().any(|&count| count > max));
+        let missing = self.required & !(present | open);
+        !too_many && present & self.banned == 0 && missing == 0
+    }
+
+    pub fn matches<const N: usize>(&self, solution: &Solution<N>) -> bool {
+        let letters = solution
+            .rows
+            .iter()
+            .flat_map(|row| row.0.map(AsciiChar::as_byte));
+        self.could_hold(letters, 0)
+    }
+}
+
+/// Written as the options that give these rules, like `--max-repeats 2 --ban-letters qz`
+impl Display for LetterRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let letters = |mask: u32| {
+            (b'a'..=b'z')
+                .filter(|&letter| mask & letter_bit(letter) != 0)
+                .map(char::from)
+                .collect::<String>()
+        };
+        let mut options = vec![];
+        if let Some(max) = self.max_repeats {
+            options.push(format!("--max-repeats {max}"));
+        }
+        for (option, mask) in [
+            ("--ban-letters", self.banned),
+            ("--require-letters", self.required),
+        ] {
+            if mask != 0 {
+                options.push(format!("{option} {}", letters(mask)));
+            }
+        }
+        f.write_str(&options.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_required_words() {
+        for text in ["grime", "grime@row2", "grime@column5"] {
+            let required: RequiredWord = text.parse().unwrap();
+            assert_eq!(required.to_string(), text);
+        }
+        assert_eq!(
+            "grime@row2".parse::<RequiredWord>().unwrap().slot,
+            Slot::Row(1)
+        );
+        assert!("grime@row0".parse::<RequiredWord>().is_err());
+        assert!("grime@middle".parse::<RequiredWord>().is_err());
+        assert!("Grime".parse::<RequiredWord>().is_err());
+    }
+
+    #[test]
+    fn required_words_have_to_fit_the_grid() {
+        let rules = |text: &str| {
+            let mut rules = WordRules::default();
+            rules.require(text.parse().unwrap());
+            rules
+        };
+
+        assert_eq!(rules("grime@column5").check(5), Ok(()));
+        assert!(rules("grime@row6").check(5).is_err());
+        assert!(rules("grime@column9").check(5).is_err());
+        assert!(rules("grime").check(4).is_err());
+        assert_eq!(rules("grim@row4").check(4), Ok(()));
+    }
+
+    #[test]
+    fn matches_solutions_with_every_required_word_and_no_forbidden_ones() {
+        let sol = Solution::new(["grime", "honor", "outdo", "steed", "terse"]);
+        let rules = |required: &[&str], forbidden: &[&str]| {
+            let mut rules = WordRules::default();
+            for word in required {
+                rules.require(word.parse().unwrap());
+            }
+            for word in forbidden {
+                rules.forbid(*word);
+            }
+            rules
+        };
+
+        assert!(WordRules::NONE.matches(&sol));
+        assert!(rules(&["outdo", "ghost"], &["level"]).matches(&sol));
+        assert!(rules(&["honor@row2", "route@column2"], &[]).matches(&sol));
+        assert!(!rules(&["honor@row3"], &[]).matches(&sol));
+        assert!(!rules(&["honor@column2"], &[]).matches(&sol));
+        assert!(!rules(&["level"], &[]).matches(&sol));
+        assert!(!rules(&[], &["modes"]).matches(&sol));
+    }
+
+    #[test]
+    fn partly_filled_grids_hold_words_that_still_fit_an_empty_slot() {
+        let mut rules = WordRules::default();
+        rules.require("honor@row2".parse().unwrap());
+        let first_row = |y: usize, x: usize| (y == 0).then(|| b"grime"[x]);
+        let first_column = |y: usize, x: usize| (x == 0).then(|| b"ghost"[y]);
+
+        assert!(rules.could_hold::<5>(first_row));
+        assert!(rules.could_hold::<5>(first_column));
+        assert!(!rules.transpose().could_hold::<5>(first_row));
+    }
+
+    #[test]
+    fn letter_rules_limit_repeats_and_ban_or_require_letters() {
+        let sol = Solution::new(["grime", "honor", "outdo", "steed", "terse"]);
+        let letters =
+            |text: &'static str| text.chars().map(|ch| AsciiChar::from_ascii(ch).unwrap());
+        let banning = |text| {
+            let mut rules = LetterRules::default();
+            letters(text).for_each(|letter| rules.ban(letter));
+            rules
+        };
+        let requiring = |text| {
+            let mut rules = LetterRules::default();
+            letters(text).for_each(|letter| rules.require(letter));
+            rules
+        };
+        let at_most = |max| LetterRules {
+            max_repeats: Some(max),
+            ..Default::default()
+        };
+
+        assert!(LetterRules::default().matches(&sol));
+        assert!(banning("qxz").matches(&sol));
+        assert!(!banning("qxg").matches(&sol));
+        assert!(!banning("g").allows("grime"));
+        assert!(requiring("ghu").matches(&sol));
+        assert!(!requiring("ga").matches(&sol));
+        assert!(at_most(5).matches(&sol));
+        assert!(!at_most(4).matches(&sol));
+    }
+
+    #[test]
+    fn required_letters_can_still_go_in_open_squares() {
+        let mut rules = LetterRules::default();
+        rules.require(AsciiChar::z);
+
+        assert!(!rules.could_hold(*b"grime", 0));
+        assert!(rules.could_hold(*b"grime", letter_bit(b'z')));
+        assert!(rules.could_hold(*b"zebra", 0));
+    }
+}
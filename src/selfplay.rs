@@ -0,0 +1,385 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+use rand::{Rng, seq::SliceRandom};
+#[cfg(feature = "multi-thread")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    Solution, Word,
+    finder::{Puzzle, PuzzleViewModel},
+    solver::CandidateSet,
+};
+
+/// A way of picking guesses in a puzzle
+pub trait Strategy {
+    /// Starts playing a new puzzle, knowing only that it's one of the strategy's candidates
+    fn new_game(&self) -> Box<dyn Player + '_>;
+}
+
+/// A [`Strategy`] part way through one game, keeping track of what it's been shown
+pub trait Player {
+    /// The word to guess next, or `None` if it has nothing left to try
+    fn next_guess(&self) -> Option<Word>;
+
+    /// Takes in the `view` the puzzle showed after `guess`
+    fn saw(&mut self, guess: &Word, view: &PuzzleViewModel);
+}
+
+/// Guesses whichever word splits the solutions still possible most evenly, using
+/// [`CandidateSet::next_guess`]
+pub struct Entropy {
+    candidates: CandidateSet,
+    dictionary: Vec<Word>,
+}
+
+impl Strategy for Entropy {
+    fn new_game(&self) -> Box<dyn Player + '_> {
+        Box::new(EntropyPlayer {
+            candidates: self.candidates.clone(),
+            dictionary: &self.dictionary,
+        })
+    }
+}
+
+struct EntropyPlayer<'a> {
+    candidates: CandidateSet,
+    dictionary: &'a [Word],
+}
+
+impl Player for EntropyPlayer<'_> {
+    fn next_guess(&self) -> Option<Word> {
+        self.candidates.next_guess(self.dictionary)
+    }
+
+    fn saw(&mut self, guess: &Word, view: &PuzzleViewModel) {
+        self.candidates.narrow(guess, view);
+    }
+}
+
+/// Guesses a missing row of the first solution still possible, as a baseline for the others
+pub struct FirstCandidate {
+    candidates: CandidateSet,
+}
+
+impl Strategy for FirstCandidate {
+    fn new_game(&self) -> Box<dyn Player + '_> {
+        Box::new(FirstCandidatePlayer {
+            candidates: self.candidates.clone(),
+        })
+    }
+}
+
+struct FirstCandidatePlayer {
+    candidates: CandidateSet,
+}
+
+impl Player for FirstCandidatePlayer {
+    fn next_guess(&self) -> Option<Word> {
+        let solution = self.candidates.members().next()?;
+        let missing = self.candidates.missing_row()?;
+        Some(solution.rows[missing].clone())
+    }
+
+    fn saw(&mut self, guess: &Word, view: &PuzzleViewModel) {
+        self.candidates.narrow(guess, view);
+    }
+}
+
+/// Every strategy self-play can pick by name
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StrategyKind {
+    #[default]
+    Entropy,
+    FirstCandidate,
+}
+
+impl StrategyKind {
+    pub const ALL: [Self; 2] = [Self::Entropy, Self::FirstCandidate];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Entropy => "entropy",
+            Self::FirstCandidate => "first-candidate",
+        }
+    }
+
+    /// Builds this kind of strategy, knowing the puzzle is one of `candidates` and guessing
+    /// from `dictionary`
+    pub fn create(
+        self,
+        candidates: Vec<Solution>,
+        dictionary: Vec<Word>,
+    ) -> Box<dyn Strategy + Sync> {
+        let candidates = CandidateSet::new(candidates);
+        match self {
+            Self::Entropy => Box::new(Entropy {
+                candidates,
+                dictionary,
+            }),
+            Self::FirstCandidate => Box::new(FirstCandidate { candidates }),
+        }
+    }
+}
+
+impl Display for StrategyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(Self::name).join(", ");
+                format!("unknown strategy {s:?}, expected one of {names}")
+            })
+    }
+}
+
+/// Every row and column word of `solutions`, each once
+pub fn dictionary_of(solutions: &[Solution]) -> Vec<Word> {
+    solutions
+        .iter()
+        .flat_map(|sol| sol.rows.iter().cloned().chain(sol.columns()))
+        .sorted()
+        .dedup()
+        .collect()
+}
+
+/// `count` of the solutions picked at random, or all of them if there aren't that many
+pub fn sample(solutions: &[Solution], count: usize, rng: &mut impl Rng) -> Vec<Solution> {
+    solutions.choose_multiple(rng, count).cloned().collect()
+}
+
+/// How one game went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub solution: Solution,
+    pub guesses: usize,
+    /// Whether the grid was filled before running out of guesses or ideas
+    pub solved: bool,
+    pub time: Duration,
+}
+
+/// Plays `solution` with `strategy` until the grid is filled, giving up after `max_guesses` or
+/// once the strategy has nothing to guess
+pub fn play(solution: &Solution, strategy: &dyn Strategy, max_guesses: usize) -> Game {
+    let start = Instant::now();
+    let mut puzzle = Puzzle::new(solution.clone());
+    let mut player = strategy.new_game();
+    let mut guesses = 0;
+    let mut solved = puzzle.view().is_finished;
+    while !solved && guesses < max_guesses {
+        let Some(guess) = player.next_guess() else {
+            break;
+        };
+        puzzle.guess(guess.clone());
+        guesses += 1;
+        let view = puzzle.view();
+        player.saw(&guess, &view);
+        solved = view.is_finished;
+    }
+    Game {
+        solution: solution.clone(),
+        guesses,
+        solved,
+        time: start.elapsed(),
+    }
+}
+
+/// Plays every one of `solutions` with `strategy`, for a report listing the `shown` worst and
+/// slowest games
+pub fn play_all(
+    solutions: &[Solution],
+    strategy: &(dyn Strategy + Sync),
+    max_guesses: usize,
+    shown: usize,
+) -> Report {
+    let game = |solution: &Solution| play(solution, strategy, max_guesses);
+
+    #[cfg(feature = "multi-thread")]
+    let games = solutions.par_iter().map(game).collect();
+
+    #[cfg(not(feature = "multi-thread"))]
+    let games = solutions.iter().map(game).collect();
+
+    Report { games, shown }
+}
+
+/// How a strategy did over a set of games
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub games: Vec<Game>,
+    /// How many of the worst and slowest games to list
+    pub shown: usize,
+}
+
+impl Report {
+    /// How many solved games took each number of guesses
+    pub fn distribution(&self) -> BTreeMap<usize, usize> {
+        self.games
+            .iter()
+            .filter(|game| game.solved)
+            .map(|game| game.guesses)
+            .counts()
+            .into_iter()
+            .collect()
+    }
+
+    /// The average guesses over the solved games
+    pub fn mean(&self) -> Option<f64> {
+        let solved = self.games.iter().filter(|game| game.solved).collect_vec();
+        let total = solved.iter().map(|game| game.guesses).sum::<usize>();
+        (!solved.is_empty()).then(|| total as f64 / solved.len() as f64)
+    }
+
+    /// The games that weren't solved, then the ones taking the most guesses
+    pub fn worst(&self) -> Vec<&Game> {
+        self.games
+            .iter()
+            .sorted_by_key(|game| (game.solved, std::cmp::Reverse(game.guesses)))
+            .take(self.shown)
+            .collect()
+    }
+
+    pub fn slowest(&self) -> Vec<&Game> {
+        self.games
+            .iter()
+            .sorted_by_key(|game| std::cmp::Reverse(game.time))
+            .take(self.shown)
+            .collect()
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let solved = self.games.iter().filter(|game| game.solved).count();
+        writeln!(f, "played {}, solved {solved}", self.games.len())?;
+        writeln!(f, "guesses  games")?;
+        for (guesses, games) in self.distribution() {
+            writeln!(f, "{guesses:>7}  {games}")?;
+        }
+        if let Some(mean) = self.mean() {
+            writeln!(f, "mean {mean:.2} guesses")?;
+        }
+        writeln!(f, "worst:")?;
+        for game in self.worst() {
+            let unsolved = if game.solved { "" } else { ", unsolved" };
+            writeln!(
+                f,
+                "  {} ({} guesses{unsolved})",
+                game.solution, game.guesses
+            )?;
+        }
+        writeln!(f, "slowest:")?;
+        for game in self.slowest() {
+            writeln!(f, "  {} ({:.2?})", game.solution, game.time)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    fn solutions() -> Vec<Solution> {
+        [
+            "aback,algae,rally,grove,hewed",
+            "aback,algae,rally,grove,hewer",
+            "abaca,baled,algae,clasp,islet",
+            "grime,honor,outdo,steed,terse",
+        ]
+        .map(|sol| sol.parse().unwrap())
+        .into()
+    }
+
+    #[test]
+    fn every_strategy_solves_every_puzzle() {
+        let solutions = solutions();
+
+        for kind in StrategyKind::ALL {
+            let strategy = kind.create(solutions.clone(), dictionary_of(&solutions));
+            let report = play_all(&solutions, strategy.as_ref(), 20, 5);
+
+            assert!(
+                report.games.iter().all(|game| game.solved),
+                "{kind} gave up:\n{report}"
+            );
+            assert_eq!(
+                report.distribution().values().sum::<usize>(),
+                solutions.len()
+            );
+        }
+    }
+
+    #[test]
+    fn gives_up_after_the_most_guesses_allowed() {
+        let solutions = solutions();
+        let strategy = StrategyKind::FirstCandidate.create(solutions.clone(), vec![]);
+
+        let game = play(&solutions[0], strategy.as_ref(), 2);
+
+        assert_eq!(game.guesses, 2);
+        assert!(!game.solved);
+    }
+
+    #[test]
+    fn reports_the_distribution_and_worst_games() {
+        let solutions = solutions();
+        let game = |i: usize, guesses, solved| Game {
+            solution: solutions[i].clone(),
+            guesses,
+            solved,
+            time: Duration::from_millis(guesses as u64),
+        };
+        let report = Report {
+            games: vec![
+                game(0, 3, true),
+                game(1, 5, true),
+                game(2, 3, true),
+                game(3, 9, false),
+            ],
+            shown: 2,
+        };
+
+        assert_eq!(report.distribution(), BTreeMap::from([(3, 2), (5, 1)]));
+        assert_eq!(report.mean(), Some(11.0 / 3.0));
+        assert_eq!(report.worst(), [&report.games[3], &report.games[1]]);
+        assert_eq!(report.slowest(), [&report.games[3], &report.games[1]]);
+    }
+
+    #[test]
+    fn samples_are_repeatable_by_seed() {
+        let solutions = solutions();
+        let pick = |seed| sample(&solutions, 2, &mut StdRng::seed_from_u64(seed));
+
+        assert_eq!(pick(3), pick(3));
+        assert_eq!(pick(3).len(), 2);
+        assert_eq!(
+            sample(&solutions, 10, &mut StdRng::seed_from_u64(3)).len(),
+            4
+        );
+    }
+
+    #[test]
+    fn strategy_names_round_trip() {
+        for kind in StrategyKind::ALL {
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+        assert!("random".parse::<StrategyKind>().is_err());
+    }
+}
@@ -0,0 +1,378 @@
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt::{Display, Write},
+    str::FromStr,
+};
+
+use crate::Solution;
+
+/// One of `count` parts of a search, numbered from 1, that each take every `count`th first row
+/// so the parts are about the same size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shard {
+    pub number: usize,
+    pub count: usize,
+}
+
+impl Default for Shard {
+    /// The whole search in one part
+    fn default() -> Self {
+        Self {
+            number: 1,
+            count: 1,
+        }
+    }
+}
+
+impl Shard {
+    /// Whether the branch under the `branch`th first row, counting from 0, is in this shard
+    pub fn contains(&self, branch: usize) -> bool {
+        branch % self.count == self.number - 1
+    }
+}
+
+impl Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.number, self.count)
+    }
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected a shard like 2/4, got {s:?}");
+        let (number, count) = s.split_once('/').ok_or_else(invalid)?;
+        let number = number.parse().map_err(|_| invalid())?;
+        let count = count.parse().map_err(|_| invalid())?;
+        if number == 0 || number > count {
+            return Err(format!(
+                "shard {s} should be between 1/{count} and {count}/{count}"
+            ));
+        }
+        Ok(Self { number, count })
+    }
+}
+
+/// A hash of everything that decides which solutions a search finds, so shards of different
+/// searches can't be merged by mistake.
+///
+/// Shards are often run on different machines, so it's FNV-1a of the settings written out as
+/// text, rather than anything that hashes numbers at the machine's own width or could change
+/// between builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    pub fn of(settings: impl Display) -> Self {
+        let mut hasher = Fnv(0xcbf29ce484222325);
+        write!(hasher, "{settings}").expect("hashing text can't fail");
+        Self(hasher.0)
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16)
+            .map(Self)
+            .map_err(|_| format!("expected a hexadecimal fingerprint, got {s:?}"))
+    }
+}
+
+struct Fnv(u64);
+
+impl Write for Fnv {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        for byte in s.bytes() {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+        Ok(())
+    }
+}
+
+/// The first line of a shard's output, saying which part of which search it holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardHeader {
+    pub shard: Shard,
+    /// How many words the search was given
+    pub words: usize,
+    /// Which finder did the search, since each takes its first rows in its own order
+    pub search: String,
+    /// The word list and every option that changes which solutions the search prints
+    pub settings: Fingerprint,
+}
+
+impl Display for ShardHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "# shard {} of {} words from {} with settings {}",
+            self.shard, self.words, self.search, self.settings
+        )
+    }
+}
+
+impl FromStr for ShardHeader {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "expected a header like \"# shard 2/4 of 5759 words from new-double-sided with \
+                 settings 8c2f0e7d5ba1c963\", got {s:?}"
+            )
+        };
+        let rest = s.strip_prefix("# shard ").ok_or_else(invalid)?;
+        let (shard, rest) = rest.split_once(" of ").ok_or_else(invalid)?;
+        let (words, rest) = rest.split_once(" words from ").ok_or_else(invalid)?;
+        let (search, settings) = rest.split_once(" with settings ").ok_or_else(invalid)?;
+        Ok(Self {
+            shard: shard.parse()?,
+            words: words.parse().map_err(|_| invalid())?,
+            search: search.to_string(),
+            settings: settings.parse()?,
+        })
+    }
+}
+
+/// The last line of a shard's output, only written once every branch in it was searched
+pub const SHARD_COMPLETE: &str = "# complete";
+
+/// A shard's output: its header, one solution per line and, if it finished, [`SHARD_COMPLETE`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardFile {
+    pub header: ShardHeader,
+    pub solutions: Vec<Solution>,
+    pub complete: bool,
+}
+
+impl FromStr for ShardFile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().enumerate().filter(|(_, line)| !line.is_empty());
+        let header = lines.next().ok_or("the file is empty")?.1.parse()?;
+        let mut file = Self {
+            header,
+            solutions: Vec::new(),
+            complete: false,
+        };
+        for (number, line) in lines {
+            if file.complete {
+                return Err(format!(
+                    "line {}: {line:?} after {SHARD_COMPLETE:?}",
+                    number + 1
+                ));
+            }
+            if line == SHARD_COMPLETE {
+                file.complete = true;
+                continue;
+            }
+            let sol = line
+                .parse()
+                .map_err(|_| format!("line {}: invalid solution {line:?}", number + 1))?;
+            file.solutions.push(sol);
+        }
+        Ok(file)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MergeError {
+    NoShards,
+    /// The shards weren't all split from the same search
+    Mismatched(ShardHeader, ShardHeader),
+    Repeated(Shard),
+    /// The numbers of the shards that weren't given
+    Missing(Vec<usize>),
+    Incomplete(Shard),
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MergeError::NoShards => write!(f, "There are no shards to merge"),
+            MergeError::Mismatched(first, other) => write!(
+                f,
+                "These shards come from different searches:\n{first}\n{other}"
+            ),
+            MergeError::Repeated(shard) => write!(f, "Shard {shard} was given more than once"),
+            MergeError::Missing(numbers) => {
+                let numbers = numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+                write!(f, "Missing shards {}", numbers.join(", "))
+            }
+            MergeError::Incomplete(shard) => {
+                write!(f, "Shard {shard} stopped before it was finished")
+            }
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+/// Checks `files` hold every shard of one search, each finished, and combines their solutions
+/// into one sorted list without duplicates
+pub fn merge(files: &[ShardFile]) -> Result<Vec<Solution>, MergeError> {
+    let first = &files.first().ok_or(MergeError::NoShards)?.header;
+    let mut seen = BTreeSet::new();
+    for file in files {
+        let header = &file.header;
+        if (
+            header.shard.count,
+            header.words,
+            &header.search,
+            header.settings,
+        ) != (
+            first.shard.count,
+            first.words,
+            &first.search,
+            first.settings,
+        ) {
+            return Err(MergeError::Mismatched(first.clone(), header.clone()));
+        }
+        if !seen.insert(header.shard.number) {
+            return Err(MergeError::Repeated(header.shard));
+        }
+        if !file.complete {
+            return Err(MergeError::Incomplete(header.shard));
+        }
+    }
+    let missing = (1..=first.shard.count)
+        .filter(|number| !seen.contains(number))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(MergeError::Missing(missing));
+    }
+
+    let solutions = files
+        .iter()
+        .flat_map(|file| file.solutions.iter().cloned())
+        .collect::<BTreeSet<_>>();
+    Ok(solutions.into_iter().collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn shard_file(shard: &str, solutions: &[&str], complete: bool) -> ShardFile {
+        let mut text = format!(
+            "# shard {shard} of 10 words from new-double-sided with settings 00000000000000ff\n"
+        );
+        for sol in solutions {
+            text += &format!("{sol}\n");
+        }
+        if complete {
+            text += SHARD_COMPLETE;
+        }
+        text.parse().unwrap()
+    }
+
+    const ROWS: &str = "ghost,route,inter,modes,erode";
+    const COLUMNS: &str = "grime,honor,outdo,steed,terse";
+
+    #[test]
+    fn shards_take_turns_at_first_rows() {
+        let shard: Shard = "2/3".parse().unwrap();
+
+        let branches = (0..8).filter(|&i| shard.contains(i)).collect::<Vec<_>>();
+
+        assert_eq!(branches, vec![1, 4, 7]);
+        assert!((0..8).all(|i| Shard::default().contains(i)));
+    }
+
+    #[test]
+    fn rejects_shards_out_of_range() {
+        assert!("0/3".parse::<Shard>().is_err());
+        assert!("4/3".parse::<Shard>().is_err());
+        assert!("3".parse::<Shard>().is_err());
+        assert_eq!("3/3".parse::<Shard>().unwrap().to_string(), "3/3");
+    }
+
+    #[test]
+    fn headers_round_trip_through_text() {
+        let header = ShardHeader {
+            shard: "2/4".parse().unwrap(),
+            words: 5759,
+            search: "double-sided-bin".to_string(),
+            settings: Fingerprint::of("grime honor"),
+        };
+
+        assert_eq!(header.to_string().parse(), Ok(header));
+    }
+
+    #[test]
+    fn merging_sorts_and_removes_duplicates() {
+        let files = [
+            shard_file("2/2", &[ROWS, COLUMNS], true),
+            shard_file("1/2", &[COLUMNS], true),
+        ];
+
+        let merged = merge(&files).unwrap();
+
+        assert_eq!(
+            merged.iter().map(|sol| sol.to_string()).collect::<Vec<_>>(),
+            vec![ROWS, COLUMNS]
+        );
+    }
+
+    #[test]
+    fn merging_needs_every_shard_finished() {
+        assert_eq!(
+            merge(&[shard_file("2/3", &[], true)]),
+            Err(MergeError::Missing(vec![1, 3]))
+        );
+        assert_eq!(
+            merge(&[
+                shard_file("1/2", &[], true),
+                shard_file("2/2", &[ROWS], false)
+            ]),
+            Err(MergeError::Incomplete("2/2".parse().unwrap()))
+        );
+        assert_eq!(
+            merge(&[shard_file("1/2", &[], true), shard_file("1/2", &[], true)]),
+            Err(MergeError::Repeated("1/2".parse().unwrap()))
+        );
+        assert!(matches!(
+            merge(&[shard_file("1/2", &[], true), shard_file("2/3", &[], true)]),
+            Err(MergeError::Mismatched(..))
+        ));
+        assert_eq!(merge(&[]), Err(MergeError::NoShards));
+    }
+
+    #[test]
+    fn merging_needs_every_shard_from_the_same_settings() {
+        let mut other = shard_file("2/2", &[ROWS], true);
+        other.header.settings = Fingerprint::of("a different template");
+
+        assert!(matches!(
+            merge(&[shard_file("1/2", &[], true), other]),
+            Err(MergeError::Mismatched(..))
+        ));
+    }
+
+    #[test]
+    fn fingerprints_are_the_same_on_every_build() {
+        assert_eq!(Fingerprint::of("").to_string(), "cbf29ce484222325");
+        assert_eq!(Fingerprint::of("a").to_string(), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn nothing_may_follow_the_complete_line() {
+        let text = format!(
+            "# shard 1/1 of 10 words from double-sided-bin with settings 00000000000000ff\n\
+             {SHARD_COMPLETE}\n{ROWS}\n"
+        );
+
+        assert!(text.parse::<ShardFile>().is_err());
+    }
+}
@@ -1,14 +1,224 @@
-#![allow(dead_code, unused)]
-use crate::{finder::Puzzle, Word};
+use std::collections::{BTreeSet, HashMap};
 
-fn next_guess(puzzle: &Puzzle) -> Word {
-    "hewed".into()
+use ascii::AsciiChar;
+
+use crate::{
+    Solution, Word,
+    feedback::Feedback,
+    finder::{LetterPlayed, Puzzle, PuzzleViewModel},
+    first_guess::entropy,
+    letter_bit,
+};
+
+/// The best word to guess next in `puzzle`, out of `dictionary`, given every solution it could
+/// have had.
+///
+/// Only the `candidates` that would have shown the same view for the guesses so far are kept.
+/// Once that leaves a single solution, the guess is whichever of its rows is still missing,
+/// otherwise it's the word that splits the rest up the most evenly, keeping the first of any
+/// ties. Returns `None` if none of the `candidates` fit the guesses, or if the grid is full.
+pub fn next_guess<const N: usize>(
+    puzzle: &Puzzle<N>,
+    candidates: &[Solution<N>],
+    dictionary: &[Word<N>],
+) -> Option<Word<N>> {
+    let view = puzzle.view();
+    let remaining = candidates
+        .iter()
+        .filter(|sol| sol.does_match(&view))
+        .collect::<Vec<_>>();
+    let guessed = letter_mask(view.guesses.iter().flat_map(|guess| guess.0));
+    best_guess(&remaining, revealed(&view), guessed, dictionary)
+}
+
+/// [`next_guess`] for the solutions left, `remaining`, given the squares already `revealed` and
+/// the letters already `guessed`
+fn best_guess<const N: usize>(
+    remaining: &[&Solution<N>],
+    revealed: u64,
+    guessed: u32,
+    dictionary: &[Word<N>],
+) -> Option<Word<N>> {
+    match remaining {
+        [] => None,
+        [solution] => missing_row::<N>(revealed).map(|y| solution.rows[y].clone()),
+        _ => {
+            let mut best = None;
+            for guess in dictionary {
+                let score = entropy(&distribution(remaining, revealed, guessed, guess));
+                if best.as_ref().is_none_or(|&(_, best)| score > best) {
+                    best = Some((guess, score));
+                }
+            }
+            best.map(|(guess, _)| guess.clone())
+        }
+    }
+}
+
+fn letter_mask(letters: impl IntoIterator<Item = AsciiChar>) -> u32 {
+    letters
+        .into_iter()
+        .map(|letter| letter_bit(letter.as_byte()))
+        .fold(0, |mask, bit| mask | bit)
+}
+
+/// The squares `view` shows, one bit each row by row
+fn revealed<const N: usize>(view: &PuzzleViewModel<N>) -> u64 {
+    let mut revealed = 0;
+    for (y, row) in view.grid.iter().enumerate() {
+        for (x, square) in row.iter().enumerate() {
+            if square.is_some() {
+                revealed |= 1 << (y * N + x);
+            }
+        }
+    }
+    revealed
+}
+
+/// The first row with a square not in `revealed`, or `None` once the grid is full
+fn missing_row<const N: usize>(revealed: u64) -> Option<usize> {
+    (0..N).find(|&y| (0..N).any(|x| revealed & (1 << (y * N + x)) == 0))
+}
+
+/// How many of `solutions` would show each different view after `guess`.
+///
+/// The solutions all show the same view so far, with the squares in `revealed` shown and the
+/// letters in `guessed` guessed. So a guess can only tell them apart by the [`Feedback`] it
+/// gives and by which letters guessed so far it leaves hidden in each row, and the solutions
+/// are bucketed on just that.
+fn distribution<const N: usize>(
+    solutions: &[&Solution<N>],
+    revealed: u64,
+    guessed: u32,
+    guess: &Word<N>,
+) -> Vec<u32> {
+    let guessed = guessed | letter_mask(guess.0);
+    let mut counts = HashMap::<_, u32>::new();
+    for sol in solutions {
+        let feedback = Feedback::new(sol, guess);
+        let hidden: [u32; N] = std::array::from_fn(|y| {
+            let hidden = (0..N)
+                .filter(|&x| revealed & (1 << (y * N + x)) == 0 && !feedback.is_revealed(y, x));
+            letter_mask(hidden.map(|x| sol.rows[y].0[x])) & guessed
+        });
+        *counts.entry((feedback, hidden)).or_default() += 1;
+    }
+    counts.into_values().collect()
+}
+
+/// The solutions a puzzle could still have, narrowed down as each guess is seen.
+///
+/// Rather than replaying every guess for every solution, each narrowing only checks the
+/// solutions left against what the latest view shows, so it gets quicker as the set shrinks.
+#[derive(Debug, Clone)]
+pub struct CandidateSet<const N: usize = 5> {
+    solutions: Vec<Solution<N>>,
+    /// The letters anywhere in each solution
+    letters: Vec<u32>,
+    /// The solutions that fit every guess so far
+    remaining: Vec<usize>,
+    /// The letters of every guess so far
+    guessed: u32,
+    /// The squares shown so far, one bit each row by row
+    revealed: u64,
+}
+
+impl<const N: usize> CandidateSet<N> {
+    pub fn new(solutions: Vec<Solution<N>>) -> Self {
+        let letters = solutions
+            .iter()
+            .map(|sol| letter_mask(sol.rows.iter().flat_map(|row| row.0)))
+            .collect();
+        Self {
+            remaining: (0..solutions.len()).collect(),
+            solutions,
+            letters,
+            guessed: 0,
+            revealed: 0,
+        }
+    }
+
+    /// Keeps only the solutions that would have shown `view` after `guess`. Every guess has to
+    /// be narrowed by in the order it was made, since each one only checks what `guess` adds.
+    pub fn narrow(&mut self, guess: &Word<N>, view: &PuzzleViewModel<N>) {
+        self.guessed |= letter_mask(guess.0);
+        self.revealed = revealed(view);
+        let hints = view
+            .hints
+            .each_ref()
+            .map(|hint| letter_mask(hint.letters()));
+        let not_in_solution = letter_mask(
+            view.alphabet
+                .iter()
+                .filter(|&(_, played)| *played == LetterPlayed::NotInSolution)
+                .map(|(&letter, _)| letter),
+        );
+
+        let fits = |i: usize| {
+            let sol = &self.solutions[i];
+            let rows_fit = (0..N).all(|y| {
+                let mut open = 0;
+                for x in 0..N {
+                    let letter = sol.rows[y].0[x];
+                    match view.grid[y][x] {
+                        Some(shown) if shown != letter => return false,
+                        Some(_) => {}
+                        None if letter == guess.0[x] => return false,
+                        None => open |= letter_bit(letter.as_byte()),
+                    }
+                }
+                open & self.guessed == hints[y]
+            });
+            rows_fit && self.guessed & !self.letters[i] == not_in_solution
+        };
+        self.remaining.retain(|&i| fits(i));
+    }
+
+    pub fn len(&self) -> usize {
+        self.remaining.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining.is_empty()
+    }
+
+    /// The solutions left, in the order they were given
+    pub fn members(&self) -> impl Iterator<Item = &Solution<N>> {
+        self.remaining.iter().map(|&i| &self.solutions[i])
+    }
+
+    /// The letters the square at row `y` and column `x` could still have
+    pub fn letters_at(&self, y: usize, x: usize) -> BTreeSet<AsciiChar> {
+        self.members().map(|sol| sol.rows[y].0[x]).collect()
+    }
+
+    /// The first row with a square that hasn't been shown yet, or `None` once the grid is full
+    pub fn missing_row(&self) -> Option<usize> {
+        missing_row::<N>(self.revealed)
+    }
+
+    /// Like [`next_guess`], for the puzzle these candidates have been narrowed by, without
+    /// checking every candidate against all of its guesses again
+    pub fn next_guess(&self, dictionary: &[Word<N>]) -> Option<Word<N>> {
+        let remaining = self.members().collect::<Vec<_>>();
+        best_guess(&remaining, self.revealed, self.guessed, dictionary)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{finder::Puzzle, Solution};
+    use crate::finder::{TEST_WORDS, new_double_sided};
+
+    fn solutions() -> Vec<Solution> {
+        [
+            "aback,algae,rally,grove,hewed",
+            "aback,algae,rally,grove,hewer",
+            "abaca,baled,algae,clasp,islet",
+        ]
+        .map(|sol| sol.parse().unwrap())
+        .into()
+    }
 
     #[test]
     fn solves_puzzle_if_one_word_is_missing() {
@@ -17,12 +227,201 @@ mod test {
 
         let expected = solution.rows[4].clone();
 
-        for word in solution.rows.into_iter().take(4) {
+        for word in solution.rows.clone().into_iter().take(4) {
             puzzle.guess(word);
         }
 
-        let actual = next_guess(&puzzle);
+        let actual = next_guess(&puzzle, &[solution], &["aback".into()]);
+
+        assert_eq!(actual, Some(expected));
+    }
+
+    #[test]
+    fn has_nothing_to_guess_once_the_grid_is_full() {
+        let solution: Solution = "aback,algae,rally,grove,hewed".parse().unwrap();
+        let mut puzzle = Puzzle::new(solution.clone());
+
+        for word in solution.rows.clone() {
+            puzzle.guess(word);
+        }
+
+        assert!(puzzle.view().is_finished);
+        assert_eq!(next_guess(&puzzle, &[solution], &["aback".into()]), None);
+    }
+
+    #[test]
+    fn guesses_the_word_that_splits_the_candidates_most() {
+        let dictionary = ["aback".into(), "algae".into(), "hewed".into()];
+        let solutions = solutions();
+
+        let mut puzzle = Puzzle::new(solutions[0].clone());
+        assert_eq!(
+            next_guess(&puzzle, &solutions, &dictionary),
+            Some("hewed".into())
+        );
+
+        puzzle.guess("aback".into());
+        assert_eq!(
+            next_guess(&puzzle, &solutions, &dictionary[..2]),
+            Some("aback".into()),
+            "every guess left splits the two candidates the same, so the first is kept"
+        );
+        assert_eq!(
+            next_guess(&puzzle, &solutions, &dictionary),
+            Some("hewed".into())
+        );
+    }
+
+    #[test]
+    fn buckets_the_candidates_the_same_as_replaying_their_views() {
+        let mut solutions = solutions();
+        solutions.extend(
+            new_double_sided::solutions(&TEST_WORDS)
+                .into_iter()
+                .map(Solution::new),
+        );
+        let mut puzzle = Puzzle::new(solutions[3].clone());
+
+        for played in ["arose", "level", "edict"] {
+            let view = puzzle.view();
+            let remaining = solutions
+                .iter()
+                .filter(|sol| sol.does_match(&view))
+                .collect::<Vec<_>>();
+            let guessed = letter_mask(view.guesses.iter().flat_map(|guess| guess.0));
+
+            for guess in TEST_WORDS.iter().chain(&["arose", "eerie"]) {
+                let mut expected = HashMap::<_, u32>::new();
+                for sol in &remaining {
+                    let mut puzzle = Puzzle::new((*sol).clone());
+                    for guess in view.guesses.iter().cloned().chain([(*guess).into()]) {
+                        puzzle.guess(guess);
+                    }
+                    *expected.entry(puzzle.view().normalized()).or_default() += 1;
+                }
+                let mut expected = expected.into_values().collect::<Vec<_>>();
+                let mut actual =
+                    distribution(&remaining, revealed(&view), guessed, &(*guess).into());
+                expected.sort();
+                actual.sort();
+
+                assert_eq!(actual, expected, "{guess} after {:?}", view.guesses);
+            }
+            puzzle.guess(played.into());
+        }
+    }
+
+    #[test]
+    fn narrowing_keeps_the_solutions_that_match_every_view() {
+        let mut solutions = solutions();
+        solutions.extend(
+            new_double_sided::solutions(&TEST_WORDS)
+                .into_iter()
+                .map(Solution::new),
+        );
+        let mut candidates = CandidateSet::new(solutions.clone());
+        let mut puzzle = Puzzle::new(solutions[3].clone());
+
+        assert_eq!(candidates.len(), solutions.len());
+        for guess in ["arose", "modes", "truck", "grime"] {
+            puzzle.guess(guess.into());
+            let view = puzzle.view();
+            candidates.narrow(&guess.into(), &view);
+
+            let expected = solutions
+                .iter()
+                .filter(|sol| sol.does_match(&view))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                candidates.members().collect::<Vec<_>>(),
+                expected,
+                "after {guess}"
+            );
+        }
+        assert_eq!(candidates.len(), 1);
+    }
+
+    #[test]
+    fn guesses_the_same_as_next_guess_once_narrowed() {
+        let mut solutions = solutions();
+        solutions.extend(
+            new_double_sided::solutions(&TEST_WORDS)
+                .into_iter()
+                .map(Solution::new),
+        );
+        let dictionary = TEST_WORDS.map(Word::from);
+        let mut candidates = CandidateSet::new(solutions.clone());
+        let mut puzzle = Puzzle::new(solutions[3].clone());
+
+        while let Some(guess) = candidates.next_guess(&dictionary) {
+            assert_eq!(
+                Some(&guess),
+                next_guess(&puzzle, &solutions, &dictionary).as_ref()
+            );
+            puzzle.guess(guess.clone());
+            candidates.narrow(&guess, &puzzle.view());
+        }
+        assert!(puzzle.view().is_finished);
+        assert_eq!(candidates.missing_row(), None);
+    }
+
+    #[test]
+    fn guesses_six_by_six_grids() {
+        let solutions = [
+            ["abacus", "bonito", "aneled", "citron", "utopia", "sodden"],
+            ["abacus", "bonito", "aneled", "citron", "utopia", "sodded"],
+            ["banana", "bandit", "candid", "dental", "rental", "mental"],
+        ]
+        .map(Solution::<6>::new);
+        let dictionary = solutions
+            .iter()
+            .flat_map(|sol| sol.rows.clone())
+            .collect::<Vec<_>>();
+        let mut candidates = CandidateSet::new(solutions.to_vec());
+        let mut puzzle = Puzzle::new(solutions[1].clone());
+
+        while let Some(guess) = candidates.next_guess(&dictionary) {
+            assert_eq!(
+                Some(&guess),
+                next_guess(&puzzle, &solutions, &dictionary).as_ref()
+            );
+            puzzle.guess(guess.clone());
+            candidates.narrow(&guess, &puzzle.view());
+        }
+        assert!(puzzle.view().is_finished);
+        assert_eq!(candidates.members().collect::<Vec<_>>(), [&solutions[1]]);
+    }
+
+    #[test]
+    fn lists_the_letters_each_square_could_have() {
+        let mut candidates = CandidateSet::new(solutions());
+        let mut puzzle = Puzzle::new(solutions()[0].clone());
+
+        assert_eq!(
+            candidates.letters_at(0, 4),
+            BTreeSet::from([AsciiChar::a, AsciiChar::k])
+        );
+
+        puzzle.guess("aback".into());
+        candidates.narrow(&"aback".into(), &puzzle.view());
+
+        assert_eq!(candidates.letters_at(0, 4), BTreeSet::from([AsciiChar::k]));
+        assert_eq!(
+            candidates.letters_at(4, 4),
+            BTreeSet::from([AsciiChar::d, AsciiChar::r])
+        );
+        assert!(!candidates.is_empty());
+    }
+
+    #[test]
+    fn gives_up_when_no_candidate_fits() {
+        let solutions = solutions();
+        let mut puzzle = Puzzle::new(solutions[2].clone());
+        puzzle.guess("islet".into());
 
-        assert_eq!(actual, expected);
+        assert_eq!(
+            next_guess(&puzzle, &solutions[..2], &["aback".into()]),
+            None
+        );
     }
 }
@@ -0,0 +1,153 @@
+use std::{fmt::Display, str::FromStr};
+
+use ascii::AsciiChar;
+
+use crate::Solution;
+
+/// Letters a solution has to have in particular squares, written as `N` rows like
+/// `g????/?????/?????/?????/????t` where `?` can be any letter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Template<const N: usize = 5> {
+    cells: [[Option<AsciiChar>; N]; N],
+}
+
+impl<const N: usize> Default for Template<N> {
+    fn default() -> Self {
+        Self {
+            cells: [[None; N]; N],
+        }
+    }
+}
+
+impl<const N: usize> Template<N> {
+    pub fn letter(&self, row: usize, col: usize) -> Option<AsciiChar> {
+        self.cells[row][col]
+    }
+
+    pub fn fix(&mut self, row: usize, col: usize, letter: AsciiChar) {
+        self.cells[row][col] = Some(letter);
+    }
+
+    /// Whether the template doesn't fix any squares at all
+    pub fn is_blank(&self) -> bool {
+        self.cells.iter().flatten().all(Option::is_none)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut transposed = Self::default();
+        for (y, row) in self.cells.iter().enumerate() {
+            for (x, letter) in row.iter().enumerate() {
+                transposed.cells[x][y] = *letter;
+            }
+        }
+        transposed
+    }
+
+    pub fn matches(&self, solution: &Solution<N>) -> bool {
+        self.cells
+            .iter()
+            .zip(solution.rows.iter())
+            .all(|(fixed, word)| {
+                fixed
+                    .iter()
+                    .zip(word.0.iter())
+                    .all(|(fixed, letter)| fixed.is_none_or(|fixed| fixed == *letter))
+            })
+    }
+}
+
+impl<const N: usize> Display for Template<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = self.cells.map(|row| {
+            row.iter()
+                .map(|letter| letter.map_or('?', AsciiChar::as_char))
+                .collect::<String>()
+        });
+        f.write_str(&rows.join("/"))
+    }
+}
+
+impl<const N: usize> FromStr for Template<N> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s.split('/').collect::<Vec<_>>();
+        if rows.len() != N {
+            return Err(format!(
+                "expected {N} rows separated by '/', got {}",
+                rows.len()
+            ));
+        }
+        let mut template = Self::default();
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != N {
+                return Err(format!("row {} should be {N} squares long: {row:?}", y + 1));
+            }
+            for (x, ch) in row.chars().enumerate() {
+                match ch {
+                    '?' => {}
+                    'a'..='z' => template.fix(y, x, AsciiChar::from_ascii(ch).unwrap()),
+                    _ => return Err(format!("row {} has {ch:?}, not a letter or '?'", y + 1)),
+                }
+            }
+        }
+        Ok(template)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_text() {
+        let text = "g????/?????/??t??/?????/????e";
+
+        let template: Template = text.parse().unwrap();
+
+        assert_eq!(template.letter(0, 0), Some(AsciiChar::g));
+        assert_eq!(template.letter(2, 2), Some(AsciiChar::t));
+        assert_eq!(template.letter(1, 1), None);
+        assert_eq!(template.to_string(), text);
+    }
+
+    #[test]
+    fn reads_templates_of_other_sizes() {
+        let template: Template<4> = "g???/????/????/???t".parse().unwrap();
+
+        assert_eq!(template.letter(3, 3), Some(AsciiChar::t));
+        assert!(
+            "g????/?????/?????/?????/?????"
+                .parse::<Template<4>>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        assert!("g????/?????/?????/?????".parse::<Template>().is_err());
+        assert!("g???/?????/?????/?????/?????".parse::<Template>().is_err());
+        assert!("G????/?????/?????/?????/?????".parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn matches_solutions_with_the_fixed_letters() {
+        let sol = Solution::new(["grime", "honor", "outdo", "steed", "terse"]);
+
+        let fits: Template = "g????/?????/??t??/?????/????e".parse().unwrap();
+        let clashes: Template = "h????/?????/?????/?????/?????".parse().unwrap();
+
+        assert!(fits.matches(&sol));
+        assert!(!clashes.matches(&sol));
+        assert!(Template::default().matches(&sol));
+    }
+
+    #[test]
+    fn transposing_moves_letters_across_the_diagonal() {
+        let template: Template = "?g???/?????/?????/?????/????t".parse().unwrap();
+
+        let transposed = template.transpose();
+
+        assert_eq!(transposed.to_string(), "?????/g????/?????/?????/????t");
+    }
+}